@@ -0,0 +1,78 @@
+use std::{path::Path, sync::mpsc::{Sender, Receiver}};
+
+use crate::{scenario::{ConfigSpec, TableSpec}, stats::CommitBreakdown, report::DatabaseStats};
+
+/// Operations the workloads need from a database engine, shared with the reader threads of the mixed workload
+pub trait StorageBackend: Sized + Sync + 'static {
+
+	/// Engine name written in the report
+	const ENGINE: &'static str;
+	/// Key under which DupSort tables store their values
+	const ZERO_KEY: &'static [u8];
+
+	type Table<'db> where Self: 'db;
+	type RoTxn<'db> where Self: 'db;
+	type RwTxn<'db> where Self: 'db;
+	type RoCursor<'txn> where Self: 'txn;
+	type RwCursor<'txn> where Self: 'txn;
+
+	/// Open the environment at `path`, creating it if it doesn't exist, without touching the tables
+	fn open_env(path: &Path, config: &ConfigSpec, tables: &[TableSpec]) -> Self;
+
+	/// Create the tables of the benchmark that don't exist yet
+	fn create_tables(&self, tables: &[TableSpec]);
+
+	/// Open the database at `path`, creating it and the tables of the benchmark if they don't exist
+	fn open(path: &Path, config: &ConfigSpec, tables: &[TableSpec]) -> Self {
+		let db = Self::open_env(path, config, tables);
+		db.create_tables(tables);
+		db
+	}
+
+	/// Erase the database at `path` and create it again with the tables of the benchmark
+	fn recreate(path: &Path, config: &ConfigSpec, tables: &[TableSpec]) -> Self {
+		if std::fs::read_dir(path).is_ok() {
+			std::fs::remove_dir_all(path).expect("Can't delete previous database");
+		}
+		Self::open(path, config, tables)
+	}
+
+	fn open_table(&self, spec: &TableSpec) -> Self::Table<'_>;
+
+	fn begin_ro(&self) -> Self::RoTxn<'_>;
+
+	fn begin_rw(&self) -> Self::RwTxn<'_>;
+
+	/// Let the Handle-Slow-Readers callback end `txn`, begun by the calling thread, instead of growing the file.
+	/// `oust` is sent to when the thread must end it, and the callback waits for `ended`. Only MDBX has such a callback
+	fn allow_oust(&self, _txn: &Self::RoTxn<'_>, _oust: Sender<()>, _ended: Receiver<()>) {}
+
+	/// Returns the time spent in each stage of the commit when the engine reports it
+	fn commit(txn: Self::RwTxn<'_>) -> Option<CommitBreakdown>;
+
+	fn put<'db>(txn: &mut Self::RwTxn<'db>, table: &Self::Table<'db>, key: &[u8], value: &[u8]);
+
+	/// Returns `true` if the key was found
+	fn get<'db>(txn: &Self::RoTxn<'db>, table: &Self::Table<'db>, key: &[u8]) -> bool;
+
+	/// Delete `value` of `key`, or every value of `key` if `None`. Returns `true` if something was deleted
+	fn delete<'db>(txn: &mut Self::RwTxn<'db>, table: &Self::Table<'db>, key: &[u8], value: Option<&[u8]>) -> bool;
+
+	fn ro_cursor<'db, 'txn>(txn: &'txn Self::RoTxn<'db>, table: &'txn Self::Table<'db>) -> Self::RoCursor<'txn>;
+
+	fn rw_cursor<'db, 'txn>(txn: &'txn mut Self::RwTxn<'db>, table: &'txn Self::Table<'db>) -> Self::RwCursor<'txn>;
+
+	/// Position the cursor on the exact key/value pair. Returns `true` if it was found
+	fn seek(cursor: &mut Self::RoCursor<'_>, key: &[u8], value: &[u8]) -> bool;
+
+	fn cursor_put(cursor: &mut Self::RwCursor<'_>, key: &[u8], value: &[u8]);
+
+	/// Length of the data file on disk
+	fn file_size(&self) -> u64;
+
+	/// Bytes of the pages up to the last one in use, which unlike the file length doesn't depend on how each engine allocates the file
+	fn used_size(&self) -> u64;
+
+	/// Environment statistics and tree statistics of every table
+	fn stats(&self, tables: &[TableSpec]) -> DatabaseStats;
+}
@@ -0,0 +1,87 @@
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use crate::scenario::{ChainSpec, BLOCK_INFO_SIZE, RCT_OUTPUT_SIZE};
+
+/// Position of the tables in `ChainSpec::tables`
+pub const BLOCK_INFOS: usize = 0;
+pub const BLOCK_BLOBS: usize = 1;
+pub const BLOCK_HEIGHTS: usize = 2;
+pub const KEY_IMAGES: usize = 3;
+pub const RCT_OUTPUTS: usize = 4;
+pub const TX_BLOBS: usize = 5;
+pub const TX_IDS: usize = 6;
+pub const TX_HEIGHTS: usize = 7;
+pub const TX_OUTPUTS: usize = 8;
+
+/// Height of the next block, and the first transaction & output indices it uses
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChainTip {
+	pub height: u64,
+	pub tx: u64,
+	pub output: u64,
+}
+
+/// Key/value pairs written by a block, with the position of their table
+pub struct Block {
+	pub writes: Vec<(usize, Vec<u8>, Vec<u8>)>,
+	/// Tip once the block is applied
+	pub next: ChainTip,
+}
+
+/// Deterministic generator of the blocks of a simulated chain, the block on top of a tip is the same every time it is generated.
+/// Heights, transaction & output indices are native-endian integer keys, like in Cuprate
+pub struct Chain<'a> {
+	pub spec: &'a ChainSpec,
+	seed: u64,
+}
+
+impl<'a> Chain<'a> {
+
+	pub fn new(spec: &'a ChainSpec, seed: u64) -> Chain<'a> {
+		Chain { spec, seed }
+	}
+
+	/// Other blocks at the same heights, fork 0 is the chain itself
+	pub fn fork(&self, fork: u64) -> Chain<'a> {
+		Chain { spec: self.spec, seed: self.seed ^ fork.rotate_right(16) }
+	}
+
+	/// Block on top of `tip`, with the output of its miner transaction and its `txs_per_block` transactions
+	pub fn block(&self, tip: ChainTip) -> Block {
+		let mut rng = StdRng::seed_from_u64(self.seed ^ tip.height);
+		let height = tip.height.to_ne_bytes().to_vec();
+		let txs = self.spec.txs_per_block.sample(&mut rng) as u64;
+
+		let blob_size = self.spec.block_blob_size.sample(&mut rng) + 32 * txs as usize;
+		let mut writes = vec![
+			(BLOCK_INFOS, height.clone(), random(&mut rng, BLOCK_INFO_SIZE)),
+			(BLOCK_BLOBS, height.clone(), random(&mut rng, blob_size)),
+			(BLOCK_HEIGHTS, random(&mut rng, 32), height.clone()),
+			(RCT_OUTPUTS, tip.output.to_ne_bytes().to_vec(), random(&mut rng, RCT_OUTPUT_SIZE)),
+		];
+
+		let mut output = tip.output + 1;
+		for tx in tip.tx..tip.tx + txs {
+			let tx_id = tx.to_ne_bytes().to_vec();
+			let inputs = self.spec.inputs_per_tx.sample(&mut rng);
+			let outputs = self.spec.outputs_per_tx.sample(&mut rng) as u64;
+			let blob_size = self.spec.tx_blob_size.sample(&mut rng);
+
+			writes.push((TX_IDS, random(&mut rng, 32), tx_id.clone()));
+			writes.push((TX_HEIGHTS, tx_id.clone(), height.clone()));
+			writes.push((TX_BLOBS, tx_id.clone(), random(&mut rng, blob_size)));
+			writes.extend((0..inputs).map(|_| (KEY_IMAGES, random(&mut rng, 32), Vec::new())));
+			writes.push((TX_OUTPUTS, tx_id, (output..output + outputs).flat_map(u64::to_ne_bytes).collect()));
+			writes.extend((output..output + outputs).map(|index| (RCT_OUTPUTS, index.to_ne_bytes().to_vec(), random(&mut rng, RCT_OUTPUT_SIZE))));
+			output += outputs;
+		}
+
+		Block { writes, next: ChainTip { height: tip.height + 1, tx: tip.tx + txs, output } }
+	}
+}
+
+fn random(rng: &mut StdRng, size: usize) -> Vec<u8> {
+	let mut buf = vec![0u8; size];
+	rng.fill_bytes(&mut buf);
+	buf
+}
@@ -0,0 +1,100 @@
+use std::{path::PathBuf, process::{Command, Stdio}, io::{BufRead, BufReader, Write}, time::Instant};
+
+use rand::{Rng, RngCore};
+
+use crate::{backend::StorageBackend, scenario::{ConfigSpec, TableSpec}, report::CrashRound, workload::{Entries, table_rng}};
+
+/// Put every table like the put workload, printing the number of committed transactions after every commit.
+/// Runs in the child process killed by `run_round`
+pub fn run_child<B: StorageBackend>(config: &ConfigSpec, tables: &[TableSpec], mut path: PathBuf, seed: u64, round: u64) {
+	path.push(format!("benchmark.{}", B::ENGINE.to_lowercase()));
+	let db = B::recreate(&path, config, tables);
+
+	let mut stdout = std::io::stdout().lock();
+	let mut committed = 0u64;
+
+	for entries in table_entries(tables, seed, round) {
+		let table = db.open_table(entries.spec);
+		let is_dup = entries.spec.is_dup();
+		let job_divide = config.batch_entries(entries.spec);
+
+		for batch in 0..entries.batches(job_divide) {
+			let data = entries.batch(batch, job_divide);
+			let mut rw_tx = db.begin_rw();
+			if is_dup {
+				let mut cursor = B::rw_cursor(&mut rw_tx, &table);
+				data.iter().for_each(|data| B::cursor_put(&mut cursor, B::ZERO_KEY, &data.1));
+			} else {
+				data.iter().for_each(|data| B::put(&mut rw_tx, &table, &data.0, &data.1));
+			}
+			B::commit(rw_tx);
+
+			// Only acknowledged once the commit returned
+			committed += 1;
+			writeln!(stdout, "{committed}").and_then(|_| stdout.flush()).expect("Parent process is gone");
+		}
+	}
+}
+
+/// Start `child` (running `run_child` with the same arguments), kill it with SIGKILL after a random number of commits,
+/// then reopen the database and check which transactions survived
+pub fn run_round<B: StorageBackend>(config: &ConfigSpec, tables: &[TableSpec], mut path: PathBuf, seed: u64, round: u64, mut child: Command) -> CrashRound {
+	path.push(format!("benchmark.{}", B::ENGINE.to_lowercase()));
+
+	let entries = table_entries(tables, seed, round);
+	let total = entries.iter().map(|entries| entries.batches(config.batch_entries(entries.spec)) as u64).sum::<u64>();
+	let kill_after = table_rng(seed, round, tables.len()).gen_range(1..=total);
+
+	let mut child = child.stdout(Stdio::piped()).spawn().expect("Can't start the crash child process");
+
+	// The child keeps committing until the signal lands
+	let mut acknowledged = 0;
+	for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+		acknowledged = line.expect("Can't read the crash child process").parse().expect("Invalid commit count");
+		if acknowledged >= kill_after {
+			break;
+		}
+	}
+	// Fails if the child already exited, after its last commit
+	let _ = child.kill();
+	let status = child.wait().unwrap();
+	assert!(status.success() || status.code().is_none(), "Crash child process failed with {status}");
+
+	// Reopening runs the recovery of the engine. The child committed the tables with its first transaction, so none are created
+	let instant = Instant::now();
+	let db = B::open_env(&path, config, tables);
+	let recovery_seconds = instant.elapsed().as_secs_f64();
+
+	// Transactions must be either fully present or absent, and present ones must come before the first absent one
+	let opened: Vec<B::Table<'_>> = entries.iter().map(|entries| db.open_table(entries.spec)).collect();
+	let ro_tx = db.begin_ro();
+	let (mut recovered, mut partial, mut gap) = (0, 0, false);
+
+	entries.iter().zip(&opened).for_each(|(entries, table)| {
+		let mut cursor = entries.spec.is_dup().then(|| B::ro_cursor(&ro_tx, table));
+		let job_divide = config.batch_entries(entries.spec);
+
+		for batch in 0..entries.batches(job_divide) {
+			let data = entries.batch(batch, job_divide);
+			let present = data.iter().filter(|data| {
+				match cursor.as_mut() {
+					Some(cursor) => B::seek(cursor, B::ZERO_KEY, &data.1),
+					None => B::get(&ro_tx, table, &data.0),
+				}
+			}).count() as u64;
+
+			match present {
+				0 => gap = true,
+				present if present == data.len() as u64 && !gap => recovered += 1,
+				_ => partial += 1,
+			}
+		}
+	});
+
+	CrashRound { round, acknowledged, recovered, lost: acknowledged.saturating_sub(recovered), partial, recovery_seconds }
+}
+
+/// Same data as the iteration `round` of the benchmark
+fn table_entries(tables: &[TableSpec], seed: u64, round: u64) -> Vec<Entries<'_>> {
+	tables.iter().enumerate().map(|(i, table)| Entries::new(table, table_rng(seed, round, i).next_u64())).collect()
+}
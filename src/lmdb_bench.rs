@@ -0,0 +1,186 @@
+use std::{path::{Path, PathBuf}, fs::File};
+
+use lmdb::{Environment, EnvironmentFlags, DatabaseFlags, Database, WriteFlags, Transaction, Cursor, RoTransaction, RwTransaction, RoCursor, RwCursor};
+
+use crate::{MAX_MAP_SIZE, MAX_READERS, backend::StorageBackend, scenario::{ConfigSpec, TableSpec, TableFlag, MapKind, SyncKind}, stats::CommitBreakdown, report::{DatabaseStats, MapGeometry, TableStats}};
+
+pub struct LmdbBackend {
+	env: Environment,
+	path: PathBuf,
+}
+
+impl StorageBackend for LmdbBackend {
+
+	const ENGINE: &'static str = "LMDB";
+	/// LMDB doesn't accept zero-length keys, use the same 8 bytes zero key as monerod's `zerokval`
+	const ZERO_KEY: &'static [u8] = &[0u8; 8];
+
+	type Table<'db> = Database;
+	type RoTxn<'db> = RoTransaction<'db>;
+	type RwTxn<'db> = RwTransaction<'db>;
+	type RoCursor<'txn> = RoCursor<'txn>;
+	type RwCursor<'txn> = RwCursor<'txn>;
+
+	fn open_env(path: &Path, config: &ConfigSpec, tables: &[TableSpec]) -> Self {
+
+		std::fs::create_dir_all(path).expect("Can't create database directory");
+
+		// With MDB_WRITEMAP the data file is truncated to the map size, so keep it close to what the tables need, larger updates included
+		let map_size = tables.iter()
+			.map(|t| t.entries * (t.key_size.max() + t.value_size.max().max(config.update.apply(t.value_size).max())) * 2)
+			.sum::<usize>()
+			.saturating_add(1024usize.pow(3))
+			.min(MAX_MAP_SIZE);
+
+		// Open the database
+		let env = Environment::new().set_max_dbs(14).set_max_readers(MAX_READERS as u32)
+		.set_flags(environment_flags(config.kind, config.sync_mode))
+		.set_map_size(map_size)
+		.open(path)
+		.expect("failed to open database");
+
+		LmdbBackend { env, path: path.to_path_buf() }
+	}
+
+	fn create_tables(&self, tables: &[TableSpec]) {
+		tables.iter().for_each(|t| { self.env.create_db(Some(&t.name), database_flags(t)).unwrap(); });
+	}
+
+	fn open_table(&self, spec: &TableSpec) -> Database {
+		self.env.open_db(Some(&spec.name)).expect("failed to open table")
+	}
+
+	fn begin_ro(&self) -> RoTransaction<'_> {
+		self.env.begin_ro_txn().unwrap()
+	}
+
+	fn begin_rw(&self) -> RwTransaction<'_> {
+		self.env.begin_rw_txn().unwrap()
+	}
+
+	fn commit(txn: RwTransaction<'_>) -> Option<CommitBreakdown> {
+		txn.commit().unwrap();
+		None
+	}
+
+	fn put<'db>(txn: &mut RwTransaction<'db>, table: &Database, key: &[u8], value: &[u8]) {
+		txn.put(*table, &key, &value, WriteFlags::empty()).unwrap();
+	}
+
+	fn get<'db>(txn: &RoTransaction<'db>, table: &Database, key: &[u8]) -> bool {
+		match txn.get(*table, &key) {
+			Ok(_) => true,
+			Err(lmdb::Error::NotFound) => false,
+			Err(err) => panic!("{err}"),
+		}
+	}
+
+	fn delete<'db>(txn: &mut RwTransaction<'db>, table: &Database, key: &[u8], value: Option<&[u8]>) -> bool {
+		match txn.del(*table, &key, value) {
+			Ok(()) => true,
+			Err(lmdb::Error::NotFound) => false,
+			Err(err) => panic!("{err}"),
+		}
+	}
+
+	fn ro_cursor<'db, 'txn>(txn: &'txn RoTransaction<'db>, table: &'txn Database) -> RoCursor<'txn> {
+		txn.open_ro_cursor(*table).unwrap()
+	}
+
+	fn rw_cursor<'db, 'txn>(txn: &'txn mut RwTransaction<'db>, table: &'txn Database) -> RwCursor<'txn> {
+		txn.open_rw_cursor(*table).unwrap()
+	}
+
+	fn seek(cursor: &mut RoCursor<'_>, key: &[u8], value: &[u8]) -> bool {
+		match cursor.get(Some(key), Some(value), lmdb_sys::MDB_GET_BOTH) {
+			Ok(_) => true,
+			Err(lmdb::Error::NotFound) => false,
+			Err(err) => panic!("{err}"),
+		}
+	}
+
+	fn cursor_put(cursor: &mut RwCursor<'_>, key: &[u8], value: &[u8]) {
+		cursor.put(&key, &value, WriteFlags::empty()).unwrap();
+	}
+
+	/// With MDB_WRITEMAP the data file length is the whole map size
+	fn file_size(&self) -> u64 {
+		let file = File::open(self.path.join("data.mdb")).unwrap();
+		file.metadata().unwrap().len()
+	}
+
+	fn used_size(&self) -> u64 {
+		let info = self.env.info().unwrap();
+		let stat = self.env.stat().unwrap();
+		(info.last_pgno() as u64 + 1) * stat.page_size() as u64
+	}
+
+	fn stats(&self, tables: &[TableSpec]) -> DatabaseStats {
+		let info = self.env.info().unwrap();
+
+		// Tables are opened in their own transaction, and a thread can only have one read transaction
+		let handles: Vec<Database> = tables.iter().map(|spec| self.open_table(spec)).collect();
+		let ro_tx = self.begin_ro();
+		let tables = tables.iter().zip(handles)
+			.map(|(spec, table)| {
+				let stat = ro_tx.stat(table).unwrap();
+				TableStats {
+					table: spec.name.clone(),
+					depth: stat.depth(),
+					branch_pages: stat.branch_pages() as u64,
+					leaf_pages: stat.leaf_pages() as u64,
+					overflow_pages: stat.overflow_pages() as u64,
+					entries: stat.entries() as u64,
+				}
+			})
+			.collect();
+
+		// The GC (freelist) is the table 0
+		let mut gc = lmdb_sys::MDB_stat { ms_psize: 0, ms_depth: 0, ms_branch_pages: 0, ms_leaf_pages: 0, ms_overflow_pages: 0, ms_entries: 0 };
+		assert_eq!(unsafe { lmdb_sys::mdb_stat(ro_tx.txn(), 0, &mut gc) }, 0, "failed to read the GC stats");
+
+		let map_size = info.map_size() as u64;
+		DatabaseStats {
+			page_size: self.env.stat().unwrap().page_size(),
+			geometry: MapGeometry { current: map_size, upper: map_size, ..Default::default() },
+			last_page: info.last_pgno() as u64,
+			last_txn_id: info.last_txnid() as u64,
+			page_ops: None,
+			tables,
+			gc: Some(TableStats {
+				table: "gc".to_string(),
+				depth: gc.ms_depth,
+				branch_pages: gc.ms_branch_pages as u64,
+				leaf_pages: gc.ms_leaf_pages as u64,
+				overflow_pages: gc.ms_overflow_pages as u64,
+				entries: gc.ms_entries as u64,
+			}),
+			hsr_calls: None,
+		}
+	}
+}
+
+fn environment_flags(kind: MapKind, sync_mode: SyncKind) -> EnvironmentFlags {
+	let map = match kind {
+		MapKind::WriteMap => EnvironmentFlags::WRITE_MAP,
+		MapKind::NoWriteMap => EnvironmentFlags::empty(),
+	};
+	let sync = match sync_mode {
+		SyncKind::Durable => EnvironmentFlags::empty(),
+		SyncKind::NoMetaSync => EnvironmentFlags::NO_META_SYNC,
+		SyncKind::SafeNoSync => EnvironmentFlags::NO_SYNC,
+		SyncKind::UtterlyNoSync => EnvironmentFlags::NO_SYNC | EnvironmentFlags::MAP_ASYNC,
+	};
+	map | sync
+}
+
+fn database_flags(spec: &TableSpec) -> DatabaseFlags {
+	spec.flags.iter().fold(DatabaseFlags::empty(), |flags, flag| flags | match flag {
+		TableFlag::ReverseKey => DatabaseFlags::REVERSE_KEY,
+		TableFlag::DupSort => DatabaseFlags::DUP_SORT,
+		TableFlag::IntegerKey => DatabaseFlags::INTEGER_KEY,
+		TableFlag::DupFixed => DatabaseFlags::DUP_FIXED,
+		TableFlag::IntegerDup => DatabaseFlags::INTEGER_DUP,
+		TableFlag::ReverseDup => DatabaseFlags::REVERSE_DUP,
+	})
+}
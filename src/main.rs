@@ -2,54 +2,48 @@
 extern crate libmdbx;
 extern crate rand;
 
-use std::{sync::atomic::AtomicU64, sync::Arc, time::{Duration, Instant}, process::exit, io::{stdin}, path::{Path, PathBuf}, thread::spawn, fs::File, fmt::Write};
+use std::{time::Duration, process::{exit, Command}, io::{stdin, IsTerminal}, path::PathBuf, thread::spawn, fmt::Write, sync::{Arc, Mutex}};
 use indicatif::{ProgressStyle, ProgressState, ProgressBar};
-use libmdbx::{DatabaseBuilder, WriteMap, TableFlags, WriteFlags, Geometry, SyncMode, DatabaseFlags, Mode, DatabaseKind, NoWriteMap};
+use libmdbx::{WriteMap, NoWriteMap};
 use clap::Parser;
-use mdbx_bench::{benchmark_put_large_table, benchmark_put_small_table, recreate_db, benchmark_read_large_table, benchmark_read_small_table, benchmark_put_small_table_dup, recreate_db_dup, benchmark_read_small_table_dup};
-use serde::{Serialize};
-
+use rand::{rngs::StdRng, RngCore};
+use backend::StorageBackend;
+use chain::Chain;
+use mdbx_bench::MdbxBackend;
+use lmdb_bench::LmdbBackend;
+use workload::{benchmark_put_table, benchmark_read_table, benchmark_mixed_tables, benchmark_read_scaling, benchmark_long_read, benchmark_prune_table, benchmark_block_import, benchmark_reorg, delete_batches, thread_counts, table_rng, Entries, REORGS};
+use scenario::{Scenario, ConfigSpec, TableSpec, ChainSpec, MapKind, Workload};
+use report::CrashRound;
+use stats::Latencies;
+use report::{Report, BenchmarkReport, ConfigReport, IterationReport, PruneReport, PruneSnapshot, Phase};
+
+pub mod backend;
+pub mod chain;
+pub mod crash;
 pub mod mdbx_bench;
+pub mod lmdb_bench;
+pub mod report;
+pub mod resources;
+pub mod scenario;
+pub mod stats;
+pub mod workload;
 
-const BENCHMARK_RANGE: u64 = 3;
-const BENCHMARK_MAX: u64 = 3000000; 
-const DB_PATH: &str = "benchmark.mdbx";
 const JOB_DIVIDE: u64 = 1000;
 const MAX_MAP_SIZE: usize = 1024usize.pow(4)*4;
-const GROWTH_STEP: Option<isize> = None;
-const TICK_CHARS: &str = "⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ ";
-
-const BENCHMARK_TABLES: [&str; 2] =
-[
-	"sim_blockheight",
-	"sim_blocks",
-];
-
-const BENCHMARK_TABLES_KEY_SIZE: [usize; 2] = 
-[
-	8, 
-	32
-];
-
-const BENCHMARK_TABLES_DATA_SIZE: [usize; 2] = 
-[
-	32,
-	60*1024,
-];
-
-const BENCHMARK_TABLES_COMMITS: [usize; 2] =
-[
-	3000000,
-	40000,
-];
+/// Bytes the MDBX file grows by, unless a configuration sets its own `growth_step`
+const GROWTH_STEP: isize = 1024isize.pow(2)*256;
+/// Reader slots of the environments, bounds the reader threads of a configuration
+const MAX_READERS: usize = 128;
+/// Exit code when the user (or a non-interactive stdin) doesn't consent to the benchmark
+const EXIT_REFUSED: i32 = 3;
 
 #[derive(Parser, Debug)]
 #[command(author, version , about, long_about = None)]
 struct Args {
 	/// Path to use to create the database
-	#[arg(long)]
-	path: PathBuf,
-	/// Useless do not enable
+	#[arg(long, required_unless_present = "import")]
+	path: Option<PathBuf>,
+	/// Enable lmdb benchmark
 	#[arg(long,  default_value_t = false)]
 	lmdb: bool,
 	/// Enable mdbx benchmark
@@ -58,12 +52,27 @@ struct Args {
 	/// Benchmark with a different job division value
 	#[arg(long, default_value_t = false)]
 	extended: bool,
-}
-
-#[derive(Serialize)]
-struct Benchmark {
-	name: String,
-	durations_size: Vec<(Vec<([f64; 2], u64)>, String)>,
+	/// Scenario file (.toml or .json) describing the workload to run
+	#[arg(long)]
+	scenario: Option<PathBuf>,
+	/// Don't ask for confirmation, for scripts and unattended runs
+	#[arg(long, visible_alias = "non-interactive", default_value_t = false)]
+	yes: bool,
+	/// Seed of the data generation, a random one is used (and written in the report) when not given
+	#[arg(long)]
+	seed: Option<u64>,
+	/// Multiply the entry count of every table, e.g. 0.01 for a smoke test or 10 for a bigger database
+	#[arg(long, default_value_t = 1.0)]
+	scale: f64,
+	/// Kill the put workloads at a random commit and verify what the database holds once reopened, instead of benchmarking
+	#[arg(long, default_value_t = false)]
+	crash: bool,
+	/// Run the put workload of `ENGINE:BENCHMARK:CONFIGURATION:ROUND` until killed, used by --crash
+	#[arg(long, hide = true)]
+	crash_child: Option<String>,
+	/// Convert a report (including the tuple based reports of older versions) to the current schema, print it and exit
+	#[arg(long)]
+	import: Option<PathBuf>,
 }
 
 fn main() {
@@ -72,321 +81,269 @@ fn main() {
 
 	let mut args = Args::parse();
 
+	if let Some(import) = &args.import {
+		let report = Report::load(import);
+		println!("{}", serde_json::to_string_pretty(&report).unwrap());
+		return;
+	}
+	let path = args.path.clone().unwrap();
+
+	if let Some(child) = &args.crash_child {
+		let scenario = load_scenario(&args);
+		let [engine, benchmark, config, round]: [&str; 4] = child.split(':').collect::<Vec<_>>().try_into().expect("Invalid crash child");
+		let tables = &scenario.benchmarks[benchmark.parse::<usize>().unwrap()].tables;
+		let config = &scenario.benchmarks[benchmark.parse::<usize>().unwrap()].configurations[config.parse::<usize>().unwrap()];
+		let (seed, round) = (args.seed.unwrap(), round.parse().unwrap());
+		match (engine, config.kind) {
+			("MDBX", MapKind::WriteMap) => crash::run_child::<MdbxBackend<WriteMap>>(config, tables, path, seed, round),
+			("MDBX", MapKind::NoWriteMap) => crash::run_child::<MdbxBackend<NoWriteMap>>(config, tables, path, seed, round),
+			_ => crash::run_child::<LmdbBackend>(config, tables, path, seed, round),
+		}
+		return;
+	}
+
 	if !args.lmdb && !args.mdbx {
 		println!("None of the modules have been selected, benchmarking both\n");
 		(args.lmdb, args.mdbx) = (true, true);
 	}
 
+	// Load the workload
+	let scenario = load_scenario(&args);
+
 	// ---- Info & Consent ----
 
-	println!("Thanks you for doing this benchmark. Note that this benchmark is going to test 10 differents configurations for each database engine 3 times, and that the test is going write 3GB at each iteration. This is likely to hurt the lifespan of your SSD (unless your bencharmking on an HDD) and add fragmentation to your filesystem. If you've your ssd for more than 5 years you can cancel it now. The benchmark is likely to run between 30 minutes & 2 hours (if you've an hdd). In this time, please do not copy files on your disk, it will create artifacts in the report");
+	// Keys & values written by the configurations that will run on every engine, for every iteration
+	let engines = [(args.mdbx, "MDBX"), (args.lmdb, "LMDB")];
+	let configs: Vec<(&ConfigSpec, &[TableSpec])> = engines.iter().filter(|(enabled, _)| *enabled)
+		.flat_map(|(_, engine)| scenario.benchmarks.iter()
+			.flat_map(|benchmark| benchmark.configurations.iter().map(|config| (config, benchmark.tables.as_slice())))
+			.filter(|(config, _)| runs_on(config, engine, &args)))
+		.collect();
+	let bytes = configs.iter()
+		.flat_map(|(_, tables)| tables.iter())
+		.map(|table| table.entries as f64 * (table.key_size.mean() + table.value_size.mean()) as f64)
+		.sum::<f64>() * scenario.iterations as f64;
+
+	println!("Thanks you for doing this benchmark. Note that the '{}' scenario is going to test {} configurations over the {} database engines {} times, and that the test is going to write about {:.1} GB of keys & values in total. This is likely to hurt the lifespan of your SSD (unless your bencharmking on an HDD) and add fragmentation to your filesystem. If you've your ssd for more than 5 years you can cancel it now. In this time, please do not copy files on your disk, it will create artifacts in the report",
+		scenario.name, configs.len(), engines.iter().filter(|(enabled, _)| *enabled).count(), scenario.iterations, bytes / 1e9);
+
+	if !args.yes {
+		let stdin = stdin();
+		if !stdin.is_terminal() {
+			eprintln!("\nstdin is not a terminal, pass --yes to run the benchmark non-interactively");
+			exit(EXIT_REFUSED);
+		}
 
-	let stdin = stdin();
-	println!("\nAre you sure you want to continue ? [Y/N] ");
-	let mut input = String::new();
-	stdin.read_line(&mut input).unwrap();
+		println!("\nAre you sure you want to continue ? [Y/N] ");
+		let mut input = String::new();
+		stdin.read_line(&mut input).unwrap();
 
-	if input.starts_with('N') | input.starts_with('n') {
-		println!("\n Benchmark cancelled");
-		exit(0);
+		if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+			println!("\n Benchmark cancelled");
+			exit(EXIT_REFUSED);
+		}
 	}
 
-	let mut file_path = args.path.clone();
+	let mut file_path = path.clone();
 		file_path.push("mdbx_report.json");
-	if std::fs::File::open(&file_path).is_ok() {
-		std::fs::remove_file(&file_path).expect("Can't delete previous report");
-	}
-	let mut file = std::fs::File::create(&file_path).unwrap();
-
-	if args.mdbx {
-
-		// Create the benchmark struct
-		let mut benchmarks: Vec<Benchmark> = Vec::new();
-		
-		// Traditionnal transactional key/pair insert
-		let mut benchmark_mdbx_trad = Benchmark { name: String::from("Benchmark MDBX Traditionnal Key/Pair"), durations_size: Vec::new() };
-		
-		let put_benchmark1 = mdbx_benchmark_put::<WriteMap>("T K/P SM::Durable | WriteMap", args.path.clone(), SyncMode::Durable, 1000, 3);
-		benchmark_mdbx_trad.durations_size.push((put_benchmark1, "T K/P SM::Durable | WriteMap".to_string()));
-		println!("Changing SyncMode");
-		let put_benchmark2 = mdbx_benchmark_put::<WriteMap>("T K/P SM::SafeNoSync | WriteMap", args.path.clone(), SyncMode::SafeNoSync, 1000, 3);
-		benchmark_mdbx_trad.durations_size.push((put_benchmark2, "T K/P SM::SafeNoSync | WriteMap".to_string()));
-		println!("Changing to NoWriteMap");
-		let put_benchmark3 = mdbx_benchmark_put::<NoWriteMap>("T K/P SM::Durable | NoWriteMap", args.path.clone(), SyncMode::Durable, 1000, 3);
-		benchmark_mdbx_trad.durations_size.push((put_benchmark3, "T K/P SM::Durable | NoWriteMap".to_string()));
-		println!("Changing SyncMode");
-		let put_benchmark4 = mdbx_benchmark_put::<NoWriteMap>("T K/P SM::SafeNoSync | NoWriteMap", args.path.clone(), SyncMode::SafeNoSync, 1000, 3);
-		benchmark_mdbx_trad.durations_size.push((put_benchmark4, "T K/P SM::SafeNoSync | NoWriteMap".to_string()));
-		if args.extended {
-			let put_benchmark5 = mdbx_benchmark_put::<WriteMap>("T K/P SM::Durable | WriteMap | J10K", args.path.clone(), SyncMode::Durable, 10000, 3);
-			benchmark_mdbx_trad.durations_size.push((put_benchmark5, "T K/P SM::Durable | WriteMap | J10K".to_string()));
-			println!("Changing SyncMode");
-			let put_benchmark6 = mdbx_benchmark_put::<WriteMap>("T K/P SM::SafeNoSync | WriteMap | J10K", args.path.clone(), SyncMode::SafeNoSync, 10000, 3);
-			benchmark_mdbx_trad.durations_size.push((put_benchmark6, "T K/P SM::SafeNoSync | WriteMap | J10K".to_string()));
-		}
 
-		// Traditionnal transactional key/pair get
-		let read_benchmark1 = mdbx_benchmark_read::<WriteMap>("T K/P Read | WriteMap", args.path.clone(), SyncMode::UtterlyNoSync, 1000, 3);
-		benchmark_mdbx_trad.durations_size.push((read_benchmark1, "T K/P Read | WriteMap".to_string()));
-		let read_benchmark2 = mdbx_benchmark_read::<NoWriteMap>("T K/P Read | NoWriteMap", args.path.clone(), SyncMode::UtterlyNoSync, 1000, 3);
-		benchmark_mdbx_trad.durations_size.push((read_benchmark2, "T K/P Read | NoWriteMap".to_string()));
-
-		benchmarks.push(benchmark_mdbx_trad);
-		let json_trad_mdbx = serde_json::to_string(&benchmarks).unwrap();
-		std::io::Write::write_all(&mut file, json_trad_mdbx.as_bytes()).expect("Failed to write report");
-		
-		// Zerokval & dummykeys insert
-		let mut benchmark_mdbx_zkdup = Benchmark { name: String::from("Benchmark MDBX ZeroKey value w cursors"), durations_size: Vec::new() };
-		let put_benchmark1 = mdbx_benchmark_put_dup::<WriteMap>("ZKey SM::Durable | WriteMap", args.path.clone(), SyncMode::Durable, 1000, 3);
-		benchmark_mdbx_zkdup.durations_size.push((put_benchmark1, "ZKey SM::Durable | WriteMap".to_string()));
-		println!("Changing SyncMode");
-		let put_benchmark2 = mdbx_benchmark_put_dup::<WriteMap>("ZKey SM::SafeNoSync | WriteMap", args.path.clone(), SyncMode::SafeNoSync, 1000, 3);
-		benchmark_mdbx_zkdup.durations_size.push((put_benchmark2, "ZKey SM::SafeNoSync | WriteMap".to_string()));
-		println!("Changing to NoWriteMap");
-		let put_benchmark3 = mdbx_benchmark_put_dup::<NoWriteMap>("ZKey SM::Durable | NoWriteMap", args.path.clone(), SyncMode::Durable, 1000, 3);
-		benchmark_mdbx_zkdup.durations_size.push((put_benchmark3, "ZKey SM::Durable | NoWriteMap".to_string()));
-		println!("Changing SyncMode");
-		let put_benchmark4 = mdbx_benchmark_put_dup::<NoWriteMap>("ZKey SM::SafeNoSync | NoWriteMap", args.path.clone(), SyncMode::SafeNoSync, 1000, 3);
-		benchmark_mdbx_zkdup.durations_size.push((put_benchmark4, "ZKey SM::SafeNoSync | NoWriteMap".to_string()));
-		if args.extended {
-			let put_benchmark5 = mdbx_benchmark_put_dup::<WriteMap>("ZKey SM::Durable | WriteMap | J10K", args.path.clone(), SyncMode::Durable, 10000, 3);
-			benchmark_mdbx_zkdup.durations_size.push((put_benchmark5, "ZKey SM::Durable | WriteMap | J10K".to_string()));
-			println!("Changing SyncMode");
-			let put_benchmark6 = mdbx_benchmark_put_dup::<WriteMap>("ZKey SM::SafeNoSync | WriteMap | J10K", args.path.clone(), SyncMode::SafeNoSync, 10000, 3);
-			benchmark_mdbx_zkdup.durations_size.push((put_benchmark6, "ZKey SM::SafeNoSync | WriteMap | J10K".to_string()));
+	// Create the report, it is rewritten after every iteration and marked incomplete until the end
+	let seed = args.seed.unwrap_or_else(rand::random);
+	let report = Arc::new(Mutex::new(Report::new(&scenario.name, Some(seed), args.scale)));
+	report.lock().unwrap().save(&file_path);
+	#[cfg(unix)]
+	report::flush_on_sigint(report.clone(), file_path.clone());
+
+	for (_, engine) in engines.iter().filter(|(enabled, _)| *enabled) {
+
+		for (benchmark, benchmark_spec) in scenario.benchmarks.iter().enumerate() {
+
+			if !benchmark_spec.configurations.iter().any(|config| runs_on(config, engine, &args)) {
+				continue;
+			}
+			report.lock().unwrap().benchmarks.push(BenchmarkReport { engine: engine.to_string(), name: benchmark_spec.name.clone(), configurations: Vec::new() });
+
+			for (index, config) in benchmark_spec.configurations.iter().enumerate().filter(|(_, config)| runs_on(config, engine, &args)) {
+
+				report.lock().unwrap().benchmarks.last_mut().unwrap().configurations.push(ConfigReport::new(config.clone()));
+
+				if args.crash {
+					for round in 0..scenario.iterations {
+						// The child runs this binary with the same scenario, scale & seed
+						let mut child = Command::new(std::env::current_exe().expect("Can't find the benchmark executable"));
+						child.arg("--path").arg(&path).arg("--seed").arg(seed.to_string()).arg("--scale").arg(args.scale.to_string())
+							.arg("--crash-child").arg(format!("{engine}:{benchmark}:{index}:{round}"));
+						if let Some(scenario) = &args.scenario {
+							child.arg("--scenario").arg(scenario);
+						}
+
+						let crash_round: CrashRound = match (*engine, config.kind) {
+							("MDBX", MapKind::WriteMap) => crash::run_round::<MdbxBackend<WriteMap>>(config, &benchmark_spec.tables, path.clone(), seed, round, child),
+							("MDBX", MapKind::NoWriteMap) => crash::run_round::<MdbxBackend<NoWriteMap>>(config, &benchmark_spec.tables, path.clone(), seed, round, child),
+							_ => crash::run_round::<LmdbBackend>(config, &benchmark_spec.tables, path.clone(), seed, round, child),
+						};
+						println!("{engine} {} round {round}#: {} acknowledged, {} lost, {} partial, recovered in {:.3}ms", config.name, crash_round.acknowledged, crash_round.lost, crash_round.partial, crash_round.recovery_seconds * 1e3);
+
+						let mut report = report.lock().unwrap();
+						report.benchmarks.last_mut().unwrap().configurations.last_mut().unwrap().crash.push(crash_round);
+						report.save(&file_path);
+					}
+					continue;
+				}
+
+				let record = |iteration, latencies: Latencies| {
+					let mut report = report.lock().unwrap();
+					let config = report.benchmarks.last_mut().unwrap().configurations.last_mut().unwrap();
+					config.iterations.push(iteration);
+					config.latencies.merge(&latencies);
+					config.summarize();
+					report.save(&file_path);
+				};
+
+				match (*engine, config.kind) {
+					("MDBX", MapKind::WriteMap) => run_benchmark::<MdbxBackend<WriteMap>>(config, &benchmark_spec.tables, benchmark_spec.chain.as_ref(), path.clone(), scenario.iterations, seed, record),
+					("MDBX", MapKind::NoWriteMap) => run_benchmark::<MdbxBackend<NoWriteMap>>(config, &benchmark_spec.tables, benchmark_spec.chain.as_ref(), path.clone(), scenario.iterations, seed, record),
+					_ => run_benchmark::<LmdbBackend>(config, &benchmark_spec.tables, benchmark_spec.chain.as_ref(), path.clone(), scenario.iterations, seed, record),
+				};
+			}
 		}
-		
-		// Zerokval & dummykeys get
-		let read_benchmark1 = mdbx_benchmark_read_dup::<WriteMap>("ZKey Read | WriteMap", args.path.clone(), SyncMode::UtterlyNoSync, 1000, 3);
-		benchmark_mdbx_zkdup.durations_size.push((read_benchmark1, "ZKey Read | WriteMap".to_string()));
-		let read_benchmark2 = mdbx_benchmark_read_dup::<NoWriteMap>("ZKey Read | NoWriteMap", args.path.clone(), SyncMode::UtterlyNoSync, 1000, 3);
-		benchmark_mdbx_zkdup.durations_size.push((read_benchmark2, "ZKey Read | NoWriteMap".to_string()));
-		
-
-		let yaml_trad_mdbx = serde_json::to_string(&benchmarks).unwrap();
-		std::io::Write::write_all(&mut file, yaml_trad_mdbx.as_bytes()).expect("Failed to write report");
-		println!("Thanks a lot for having destroyed your disk. The final report can be found under : {}\nIf you have a Github account please post your benchmark here : https://github.com/Cuprate/mdbx_benchmark/issues. Don't forget to add the <Benchmark> label and tell us what your disk is (SSD, NVMe, HDD, USB?, MicroSD??, FloppyDisk?????). Otherwise, if you don't have a github account, you can also join our Revolt server, contact us on matrix (see cargo.toml) or directly send us an email (there are our GPG keys). You can find all these informations here : https://github.com/Cuprate/cuprate", file_path.clone().as_path().display());
-
-		benchmarks.push(benchmark_mdbx_zkdup);
-		if std::fs::File::open(&file_path).is_ok() {
-			std::fs::remove_file(&file_path).expect("Can't delete previous report");
-		}
-		let mut file = std::fs::File::create(&file_path).unwrap();
-		let json_zkdup_mdbx = serde_json::to_string(&benchmarks).unwrap();
-		std::io::Write::write_all(&mut file, json_zkdup_mdbx.as_bytes()).expect("Failed to write report");
 	}
 
-	
-}
+	let mut report = report.lock().unwrap();
+	report.incomplete = false;
+	report.summarize_batch_sweeps();
+	report.save(&file_path);
+	report.print_summary();
 
-fn mdbx_benchmark_put<R: DatabaseKind>(
-	msg: &'static str,
-	path: PathBuf,
-	sync_mode: SyncMode,
-	job_divide: u64,
-	num_iter: u64,)
-	-> Vec<([f64; 2], u64)> 
-{
-	let mut measurements: Vec<([f64; 2], u64)> = Vec::new();
-
-	(0..num_iter).for_each(|iteration| {
-
-		let mut path = path.clone();
-		path.push("benchmark.mdbx");
-
-		let shared_counter: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
-
-		let handle = spawn(move || {
-
-			let mut measurements: (Vec<f64>, u64) = (Vec::new(), 0u64);
-
-			let db = recreate_db::<R>(&path, sync_mode);
-
-			// Start RNG Thread
-			let mut rng = rand::thread_rng();
-
-			// Actual benchmark
-			let progress_bar = get_progress_bar(iteration, msg, (BENCHMARK_TABLES_COMMITS[0]+BENCHMARK_TABLES_COMMITS[1]) as u64);
-
-			let _ = benchmark_put_large_table(&progress_bar, &db, &mut rng, job_divide, &mut measurements);
-			let _ = benchmark_put_small_table(&progress_bar, &db, &mut rng, job_divide, &mut measurements);
-
-			progress_bar.finish();
-			
-			// Get the size & send the duration
-			path.push("mdbx.dat");
-			let file = File::open(path).unwrap();
-			let size = file.metadata().unwrap().len();
-			measurements.1 = size;
-			measurements
-		});
-
-		let mut res = handle.join().unwrap();
-		let array = [res.0.remove(0),res.0.remove(0)];
-		measurements.push((array,res.1))
-	});	
-	
-	measurements
+	println!("Thanks a lot for having destroyed your disk. The final report can be found under : {}\nIf you have a Github account please post your benchmark here : https://github.com/Cuprate/mdbx_benchmark/issues. Don't forget to add the <Benchmark> label and tell us what your disk is (SSD, NVMe, HDD, USB?, MicroSD??, FloppyDisk?????). Otherwise, if you don't have a github account, you can also join our Revolt server, contact us on matrix (see cargo.toml) or directly send us an email (there are our GPG keys). You can find all these informations here : https://github.com/Cuprate/cuprate", file_path.clone().as_path().display());
 }
 
-fn mdbx_benchmark_put_dup<R: DatabaseKind>(
-	msg: &'static str,
-	path: PathBuf,
-	sync_mode: SyncMode,
-	job_divide: u64,
-	num_iter: u64,)
-	-> Vec<([f64; 2], u64)> 
-{
-	let mut measurements: Vec<([f64; 2], u64)> = Vec::new();
-
-	(0..num_iter).for_each(|iteration| {
-
-		let mut path = path.clone();
-		path.push("benchmark.mdbx");
-
-		let shared_counter: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
-
-		let handle = spawn(move || {
-
-			let mut measurements: (Vec<f64>, u64) = (Vec::new(), 0u64);
-
-			let db = recreate_db_dup::<R>(&path, sync_mode);
-
-			// Start RNG Thread
-			let mut rng = rand::thread_rng();
-
-			// Actual benchmark
-			let progress_bar = get_progress_bar(iteration, msg, (BENCHMARK_TABLES_COMMITS[0]) as u64);
-
-			println!("small db");
-			let _ = benchmark_put_small_table_dup(&progress_bar, &db, &mut rng, job_divide, &mut measurements);
-			println!("large db");
-
-			progress_bar.finish();
-			
-			// Get the size & send the duration
-			path.push("mdbx.dat");
-			let file = File::open(path).unwrap();
-			let size = file.metadata().unwrap().len();
-			measurements.1 = size;
-			measurements
-		});
+/// Whether `config` runs on `engine`: extended configurations only with `--extended`, only put workloads in crash mode,
+/// since read & mixed ones write the same way, and no geometry or Handle-Slow-Readers callback on LMDB, which has neither
+fn runs_on(config: &ConfigSpec, engine: &str, args: &Args) -> bool {
+	(args.extended || !config.extended)
+		&& (!args.crash || config.workload == Workload::Put)
+		&& !(engine == "LMDB" && (config.sets_geometry() || config.hsr))
+}
 
-		let mut res = handle.join().unwrap();
-		let array = [0f64,res.0.remove(0)];
-		measurements.push((array,res.1))
-		
-	});	
-	
-	measurements
+fn load_scenario(args: &Args) -> Scenario {
+	let mut scenario = match &args.scenario {
+		Some(path) => Scenario::load(path),
+		None => Scenario::default(),
+	};
+	scenario.scale(args.scale);
+	scenario
 }
 
-fn mdbx_benchmark_read<R: DatabaseKind>(
-	msg: &'static str,
+fn run_benchmark<B: StorageBackend>(
+	config: &ConfigSpec,
+	tables: &[TableSpec],
+	chain: Option<&ChainSpec>,
 	path: PathBuf,
-	sync_mode: SyncMode,
-	job_divide: u64,
-	num_iter: u64,)
-	-> Vec<([f64; 2], u64)>
+	num_iter: u64,
+	seed: u64,
+	mut record: impl FnMut(IterationReport, Latencies),)
 {
-	let mut measurements: Vec<([f64; 2], u64)> = Vec::new();
-
 	(0..num_iter).for_each(|iteration| {
 
 		let mut path = path.clone();
-		path.push("benchmark.mdbx");
+		path.push(format!("benchmark.{}", B::ENGINE.to_lowercase()));
 
-		let shared_counter: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+		let config = config.clone();
+		let tables = tables.to_vec();
+		let chain = chain.cloned();
 
 		let handle = spawn(move || {
 
-			let mut measurements: (Vec<f64>, u64) = (Vec::new(), 0u64);
+			let mut measurements = Vec::new();
+			let mut latencies = Latencies::default();
 
-			let db = recreate_db::<R>(&path, sync_mode);
+			let db = B::recreate(&path, &config, &tables);
 
-			// Start RNG Thread
-			let mut rng = rand::thread_rng();
+			// Every table gets its own RNG, so each configuration writes & reads the same data in the same order
+			let mut rngs: Vec<StdRng> = (0..tables.len()).map(|table| table_rng(seed, iteration, table)).collect();
+			let entries: Vec<Entries> = tables.iter().zip(rngs.iter_mut()).map(|(table, rng)| Entries::new(table, rng.next_u64())).collect();
 
 			// Actual benchmark
-			let progress_bar = get_progress_bar(iteration, msg, (BENCHMARK_TABLES_COMMITS[0]+BENCHMARK_TABLES_COMMITS[1]) as u64);
+			let total_entries = match &chain {
+				Some(chain) if config.workload == Workload::Reorg => (chain.blocks + 2 * REORGS * config.reorg_depth.min(chain.blocks)) as u64,
+				Some(chain) => chain.blocks as u64,
+				None => tables.iter().map(|table| table.entries as u64).sum(),
+			};
+			let msg = format!("{} {}", B::ENGINE, config.name);
+			let progress_bar = get_progress_bar(iteration, &msg, total_entries);
+
+			let mut reorg = None;
+			if config.workload == Workload::Mixed {
+				let mut rng = table_rng(seed, iteration, tables.len());
+				benchmark_mixed_tables(&progress_bar, &db, &entries, &config, &mut rng, &mut measurements, &mut latencies);
+			} else if let Some(chain) = &chain {
+				let chain = Chain::new(chain, table_rng(seed, iteration, tables.len()).next_u64());
+				if config.workload == Workload::Reorg {
+					reorg = Some(benchmark_reorg(&progress_bar, &db, &chain, &tables, &config, &mut measurements, &mut latencies));
+				} else {
+					benchmark_block_import(&progress_bar, &db, &chain, &tables, config.job_divide, &mut measurements, &mut latencies);
+				}
+			} else {
+				entries.iter().for_each(|entries| benchmark_put_table(&progress_bar, &db, entries, Phase::Put, config.batch_entries(entries.spec), &mut measurements, &mut latencies));
+			}
 
-			let data_large = benchmark_put_large_table(&progress_bar, &db, &mut rng, job_divide, &mut measurements);
-			let data_small = benchmark_put_small_table(&progress_bar, &db, &mut rng, job_divide, &mut measurements);
 			progress_bar.finish();
-			measurements.0 = Vec::new();
-			let progress_bar = get_progress_bar(iteration, msg, (BENCHMARK_TABLES_COMMITS[0]+BENCHMARK_TABLES_COMMITS[1]) as u64);
-			benchmark_read_large_table(&progress_bar, &db, &mut rng, job_divide, data_large,&mut measurements);
-			benchmark_read_small_table(&progress_bar, &db, &mut rng, job_divide, data_small,&mut measurements);
-			progress_bar.finish();
-			
-			// Get the size & send the duration
-			path.push("mdbx.dat");
-			let file = File::open(path).unwrap();
-			let size = file.metadata().unwrap().len();
-			measurements.1 = size;
-			measurements
-		});
-
-		let mut res = handle.join().unwrap();
-		let array = [res.0.remove(0),res.0.remove(0)];
-		measurements.push((array,res.1))
-		
-	});	
-	
-	measurements
-}
-
-fn mdbx_benchmark_read_dup<R: DatabaseKind>(
-	msg: &'static str,
-	path: PathBuf,
-	sync_mode: SyncMode,
-	job_divide: u64,
-	num_iter: u64,)
-	-> Vec<([f64; 2], u64)>
-{
-	let mut measurements: Vec<([f64; 2], u64)> = Vec::new();
-
-	(0..num_iter).for_each(|iteration| {
-
-		let mut path = path.clone();
-		path.push("benchmark.mdbx");
-
-		let shared_counter: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
-
-		let handle = spawn(move || {
-
-			let mut measurements: (Vec<f64>, u64) = (Vec::new(), 0u64);
-
-			let db = recreate_db_dup::<R>(&path, sync_mode);
 
-			// Start RNG Thread
-			let mut rng = rand::thread_rng();
+			if config.workload == Workload::Read {
+				let progress_bar = get_progress_bar(iteration, &msg, total_entries);
+				entries.iter().zip(rngs.iter_mut()).for_each(|(entries, rng)| {
+					benchmark_read_table(&progress_bar, &db, entries, rng, config.job_divide, &mut measurements);
+				});
+				progress_bar.finish();
+			}
+
+			if config.workload == Workload::Update {
+				let progress_bar = get_progress_bar(iteration, &msg, total_entries);
+				entries.iter().for_each(|entries| {
+					let updates = entries.overwrite(1).resized(config.update.apply(entries.spec.value_size));
+					benchmark_put_table(&progress_bar, &db, &updates, Phase::Overwrite, config.job_divide, &mut measurements, &mut latencies);
+				});
+				progress_bar.finish();
+			}
+
+			let mut growth = Vec::new();
+			if config.workload == Workload::LongRead {
+				let progress_bar = get_progress_bar(iteration, &msg, total_entries * 2);
+				growth = benchmark_long_read(&progress_bar, &db, &entries, config.job_divide, &mut measurements, &mut latencies);
+				progress_bar.finish();
+			}
+
+			if config.workload == Workload::ReadScaling {
+				let thread_counts = thread_counts(config.max_readers());
+				let progress_bar = get_progress_bar(iteration, &msg, total_entries * thread_counts.len() as u64);
+				entries.iter().zip(rngs.iter_mut()).for_each(|(entries, rng)| {
+					benchmark_read_scaling(&progress_bar, &db, entries, rng, config.job_divide, &thread_counts, &mut measurements);
+				});
+				progress_bar.finish();
+			}
+
+			let mut prune = None;
+			if config.workload == Workload::Delete {
+				let snapshot = || PruneSnapshot { file_size: db.file_size(), stats: db.stats(&tables) };
+				let before_delete = snapshot();
+				let batches: Vec<Vec<Vec<usize>>> = entries.iter().zip(rngs.iter_mut()).map(|(entries, rng)| delete_batches(entries, &config, rng)).collect();
+				let deleted = batches.iter().flatten().map(Vec::len).sum::<usize>() as u64;
+				let progress_bar = get_progress_bar(iteration, &msg, deleted * 2);
+				entries.iter().zip(&batches).for_each(|(entries, batches)| benchmark_prune_table(&progress_bar, &db, entries, batches, Phase::Delete, &mut measurements, &mut latencies));
+				let after_delete = snapshot();
+				entries.iter().zip(&batches).for_each(|(entries, batches)| benchmark_prune_table(&progress_bar, &db, entries, batches, Phase::Reinsert, &mut measurements, &mut latencies));
+				progress_bar.finish();
+				prune = Some(PruneReport::new(before_delete, after_delete, snapshot()));
+			}
 
-			// Actual benchmark
-			let progress_bar = get_progress_bar(iteration, msg, (BENCHMARK_TABLES_COMMITS[0]) as u64);
-
-			let data_small = benchmark_put_small_table_dup(&progress_bar, &db, &mut rng, job_divide, &mut measurements);
-			progress_bar.finish();
-			measurements.0 = Vec::new();
-			let progress_bar = get_progress_bar(iteration, msg, (BENCHMARK_TABLES_COMMITS[0]) as u64);
-			benchmark_read_small_table_dup(&progress_bar, &db, &mut rng, job_divide, data_small,&mut measurements);
-			progress_bar.finish();
-			
 			// Get the size & send the duration
-			path.push("mdbx.dat");
-			let file = File::open(path).unwrap();
-			let size = file.metadata().unwrap().len();
-			measurements.1 = size;
-			measurements
+			(IterationReport { iteration, db_file_size: db.file_size(), db_used_size: Some(db.used_size()), tables: measurements, stats: Some(db.stats(&tables)), growth, prune, reorg }, latencies)
 		});
 
-		let mut res = handle.join().unwrap();
-		let array = [0f64,res.0.remove(0)];
-		measurements.push((array,res.1))
-		
+		let (iteration, latencies) = handle.join().unwrap();
+		record(iteration, latencies);
 	});	
-	
-	measurements
 }
 
 fn get_progress_bar(iteration: u64, msg: &str, len: u64) -> ProgressBar {
@@ -1,320 +1,271 @@
-use std::{time::{Duration, Instant}, path::{Path, PathBuf}, ptr};
+use std::{path::{Path, PathBuf}, borrow::Cow, fs::File, marker::PhantomData, ptr, sync::{Mutex, atomic::{AtomicU64, Ordering}, mpsc::{Sender, Receiver}}};
 
-use indicatif::ProgressBar;
-use libmdbx::{DatabaseKind, WriteFlags, Database, DatabaseBuilder, DatabaseFlags, Mode, Geometry, SyncMode, TableFlags};
-use rand::{Rng, rngs::ThreadRng, seq::SliceRandom, RngCore};
+use libc::c_int;
+use libmdbx::{DatabaseKind, Database, DatabaseBuilder, DatabaseFlags, Mode, Geometry, PageSize, SyncMode, Table, Transaction, Cursor, RO, Error};
+use mdbx_sys::{MDBX_env, MDBX_txn, MDBX_cursor, MDBX_val, MDBX_commit_latency, MDBX_envinfo, MDBX_stat, mdbx_pid_t, mdbx_tid_t};
 
-use crate::{BENCHMARK_TABLES_KEY_SIZE, BENCHMARK_TABLES_DATA_SIZE, BENCHMARK_TABLES_COMMITS, BENCHMARK_TABLES, MAX_MAP_SIZE};
+use crate::{GROWTH_STEP, MAX_MAP_SIZE, MAX_READERS, backend::StorageBackend, scenario::{ConfigSpec, TableSpec}, stats::CommitBreakdown, report::{DatabaseStats, MapGeometry, PageOps, TableStats}};
 
-pub fn recreate_db<R: DatabaseKind>(path: &Path, sync_mode: SyncMode) -> Database<R> {
+pub struct MdbxBackend<R: DatabaseKind> {
+	db: Database<R>,
+	path: PathBuf,
+	/// User context of the environment, dropped after it
+	slow_readers: Box<SlowReaders>,
+}
 
-	// Erase last database
-	if std::fs::read_dir(path).is_ok() {
-		std::fs::remove_dir_all(path).expect("Can't delete previous database");
-	}
+/// State of the Handle-Slow-Readers callback of a database
+#[derive(Default)]
+struct SlowReaders {
+	/// Calls of the callback since the database was opened
+	calls: AtomicU64,
+	/// Read transaction the callback may end
+	held: Mutex<Option<HeldReader>>,
+}
 
-	// Recreate the database
-	let mut db_builder: DatabaseBuilder<R> = libmdbx::Database::new();
-	let db = db_builder.set_max_tables(14).set_max_readers(32)
-	.set_flags(DatabaseFlags::from(Mode::ReadWrite { sync_mode }))
-	.set_geometry(Geometry { size: Some(0..MAX_MAP_SIZE), growth_step: Some(1024isize.pow(2)*256), shrink_threshold: None, page_size: None })
-	.open(path)
-	.expect("failed to open database");
-
-	// Create tables
-	let rw_tx = db.begin_rw_txn().unwrap();
-	(0..2).for_each(|t| { rw_tx.create_table(Some(BENCHMARK_TABLES[t]), TableFlags::empty()).unwrap(); });
-	rw_tx.commit().unwrap();
-	
-	db
+/// Reader slots of a `MDBX_NOTLS` environment don't record their thread, so the held reader is found by its snapshot
+struct HeldReader {
+	txn_id: u64,
+	oust: Sender<()>,
+	ended: Receiver<()>,
 }
 
-pub fn recreate_db_dup<R: DatabaseKind>(path: &Path, sync_mode: SyncMode) -> Database<R> {
+impl<R: DatabaseKind + 'static> StorageBackend for MdbxBackend<R> {
+
+	const ENGINE: &'static str = "MDBX";
+	const ZERO_KEY: &'static [u8] = &[];
+
+	type Table<'db> = Table<'db>;
+	type RoTxn<'db> = Transaction<'db, RO, R>;
+	type RwTxn<'db> = RwTxn<'db>;
+	type RoCursor<'txn> = Cursor<'txn, RO>;
+	type RwCursor<'txn> = RwCursor<'txn>;
+
+	fn open_env(path: &Path, config: &ConfigSpec, _tables: &[TableSpec]) -> Self {
+
+		// Open the database
+		let sync_mode = SyncMode::from(config.sync_mode);
+		let mut db_builder: DatabaseBuilder<R> = libmdbx::Database::new();
+		let db = db_builder.set_max_tables(14).set_max_readers(MAX_READERS as u32)
+		.set_flags(DatabaseFlags::from(Mode::ReadWrite { sync_mode }))
+		.set_geometry(Geometry {
+			size: Some(config.lower_size..MAX_MAP_SIZE),
+			growth_step: Some(config.growth_step.map_or(GROWTH_STEP, |step| step as isize)),
+			shrink_threshold: config.shrink_threshold.map(|shrink| shrink as isize),
+			page_size: config.page_size.map(PageSize::Set),
+		})
+		.open(path)
+		.expect("failed to open database");
+
+		// Oust the held reader instead of growing the file
+		let slow_readers = Box::<SlowReaders>::default();
+		check(unsafe { mdbx_sys::mdbx_env_set_userctx(db.ptr(), &*slow_readers as *const SlowReaders as *mut _) });
+		if config.hsr {
+			check(unsafe { mdbx_sys::mdbx_env_set_hsr(db.ptr(), Some(oust_laggard)) });
+		}
+
+		MdbxBackend { db, path: path.to_path_buf(), slow_readers }
+	}
 
-	// Erase last database
-	if std::fs::read_dir(path).is_ok() {
-		std::fs::remove_dir_all(path).expect("Can't delete previous database");
+	fn create_tables(&self, tables: &[TableSpec]) {
+		let rw_tx = self.db.begin_rw_txn().unwrap();
+		tables.iter().for_each(|t| { rw_tx.create_table(Some(&t.name), t.table_flags()).unwrap(); });
+		rw_tx.commit().unwrap();
 	}
 
-	// Recreate the database
-	let mut db_builder: DatabaseBuilder<R> = libmdbx::Database::new();
-	let db = db_builder.set_max_tables(14).set_max_readers(32)
-	.set_flags(DatabaseFlags::from(Mode::ReadWrite { sync_mode }))
-	.set_geometry(Geometry { size: Some(0..MAX_MAP_SIZE), growth_step: Some(1024isize.pow(2)*256), shrink_threshold: None, page_size: None })
-	.open(path)
-	.expect("failed to open database");
-
-	// Create tables
-	let rw_tx = db.begin_rw_txn().unwrap();
-	(0..2).for_each(|t| { rw_tx.create_table(Some(BENCHMARK_TABLES[t]), TableFlags::DUP_SORT.union(TableFlags::DUP_FIXED)).unwrap(); });
-	rw_tx.commit().unwrap();
-	
-	db
-}
+	fn open_table(&self, spec: &TableSpec) -> Table<'_> {
+		let ro_tx = self.db.begin_ro_txn().expect("failed to generate the ro tx");
+		let table = ro_tx.open_table(Some(&spec.name)).expect("failed to open table");
+		ro_tx.prime_for_permaopen(table);
+		ro_tx.commit_and_rebind_open_dbs().unwrap().1.remove(0)
+	}
 
-pub fn benchmark_put_small_table<R: DatabaseKind>(
-	pg: &ProgressBar,
-	db: &libmdbx::Database<R>, 
-	rng: &mut ThreadRng, 
-	job_divide: u64, 
-	measurements: &mut (Vec<f64>, u64)) 
-	-> Vec<([u8; BENCHMARK_TABLES_KEY_SIZE[0]],[u8; BENCHMARK_TABLES_DATA_SIZE[0]])>
-{
-	// Open table
-	let ro_tx = db.begin_ro_txn().expect("failed to generate the ro tx");
-	let table = ro_tx.open_table(Some(BENCHMARK_TABLES[0])).expect("failed to open table");
-	ro_tx.prime_for_permaopen(table);
-	let table = ro_tx.commit_and_rebind_open_dbs().unwrap().1.remove(0);
+	fn begin_ro(&self) -> Transaction<'_, RO, R> {
+		self.db.begin_ro_txn().unwrap()
+	}
 
-	// Generate random data
-	let data: Vec<([u8; BENCHMARK_TABLES_KEY_SIZE[0]],[u8; BENCHMARK_TABLES_DATA_SIZE[0]])> = (0..BENCHMARK_TABLES_COMMITS[0]).map(|_| rng.gen()).collect(); 
-	let mut data_iter = data.iter();
+	fn allow_oust(&self, txn: &Transaction<'_, RO, R>, oust: Sender<()>, ended: Receiver<()>) {
+		*self.slow_readers.held.lock().unwrap() = Some(HeldReader { txn_id: txn.id(), oust, ended });
+	}
 
-	let data_returned = data.clone();
+	fn begin_rw(&self) -> RwTxn<'_> {
+		let mut txn = ptr::null_mut();
+		check(unsafe { mdbx_sys::mdbx_txn_begin_ex(self.db.ptr(), ptr::null_mut(), mdbx_sys::MDBX_TXN_READWRITE, &mut txn, ptr::null_mut()) });
+		RwTxn { txn, _db: PhantomData }
+	}
 
-	// Measurement
-	let instant = Instant::now();
+	fn commit(txn: RwTxn<'_>) -> Option<CommitBreakdown> {
+		let mut latency: MDBX_commit_latency = unsafe { std::mem::zeroed() };
+		let rc = unsafe { mdbx_sys::mdbx_txn_commit_ex(txn.txn, &mut latency) };
+		// The transaction is freed by MDBX even when the commit fails
+		std::mem::forget(txn);
+		// MDBX_RESULT_TRUE means the transaction was aborted instead, nothing was written
+		assert_ne!(rc, mdbx_sys::MDBX_RESULT_TRUE, "MDBX aborted the transaction instead of committing it");
+		check(rc);
+
+		// Durations are 16.16 fixed point seconds
+		let seconds = |duration: u32| duration as f64 / 65536.0;
+		Some(CommitBreakdown {
+			commits: 1,
+			preparation: seconds(latency.preparation),
+			gc_wallclock: seconds(latency.gc_wallclock),
+			gc_cputime: seconds(latency.gc_cputime),
+			audit: seconds(latency.audit),
+			write: seconds(latency.write),
+			sync: seconds(latency.sync),
+			ending: seconds(latency.ending),
+			whole: seconds(latency.whole),
+		})
+	}
 
-	(0..(BENCHMARK_TABLES_COMMITS[0]/job_divide as usize)).for_each(|_| {
+	fn put<'db>(txn: &mut RwTxn<'db>, table: &Table<'db>, key: &[u8], value: &[u8]) {
+		check(unsafe { mdbx_sys::mdbx_put(txn.txn, table.dbi(), &val(key), &mut val(value), mdbx_sys::MDBX_UPSERT) });
+	}
 
-		let rw_tx = db.begin_rw_txn().unwrap();
-					
-		(0..job_divide).for_each(|_| {
+	fn get<'db>(txn: &Transaction<'db, RO, R>, table: &Table<'db>, key: &[u8]) -> bool {
+		txn.get::<Cow<[u8]>>(table, key).unwrap().is_some()
+	}
 
-			let data = data_iter.next().unwrap();
-			rw_tx.put(&table, data.0, data.1, WriteFlags::empty()).unwrap();
-		});
+	fn delete<'db>(txn: &mut RwTxn<'db>, table: &Table<'db>, key: &[u8], value: Option<&[u8]>) -> bool {
+		let value = value.map(val);
+		let value_ptr = value.as_ref().map_or(ptr::null(), |value| value as *const MDBX_val);
+		match unsafe { mdbx_sys::mdbx_del(txn.txn, table.dbi(), &val(key), value_ptr) } {
+			mdbx_sys::MDBX_NOTFOUND => false,
+			rc => { check(rc); true },
+		}
+	}
 
-		rw_tx.commit().unwrap();
-		pg.inc(job_divide);
-	});
+	fn ro_cursor<'db, 'txn>(txn: &'txn Transaction<'db, RO, R>, table: &'txn Table<'db>) -> Cursor<'txn, RO> {
+		txn.cursor(table).unwrap()
+	}
 
-	measurements.0.push(instant.elapsed().as_secs_f64());
-	data_returned
-}
+	fn rw_cursor<'db, 'txn>(txn: &'txn mut RwTxn<'db>, table: &'txn Table<'db>) -> RwCursor<'txn> {
+		let mut cursor = ptr::null_mut();
+		check(unsafe { mdbx_sys::mdbx_cursor_open(txn.txn, table.dbi(), &mut cursor) });
+		RwCursor { cursor, _txn: PhantomData }
+	}
+
+	fn seek(cursor: &mut Cursor<'_, RO>, key: &[u8], value: &[u8]) -> bool {
+		cursor.get_both::<()>(key, value).unwrap().is_some()
+	}
 
+	fn cursor_put(cursor: &mut RwCursor<'_>, key: &[u8], value: &[u8]) {
+		check(unsafe { mdbx_sys::mdbx_cursor_put(cursor.cursor, &val(key), &mut val(value), mdbx_sys::MDBX_UPSERT) });
+	}
 
-pub fn benchmark_put_large_table<R: DatabaseKind>(
-	pg: &ProgressBar,
-	db: &libmdbx::Database<R>, 
-	rng: &mut ThreadRng, 
-	job_divide: u64, 
-	measurements: &mut (Vec<f64>, u64)) 
-	-> Vec<([u8; BENCHMARK_TABLES_KEY_SIZE[1]],[u8; BENCHMARK_TABLES_DATA_SIZE[1]])>
-{
-	// Open table
-	let ro_tx = db.begin_ro_txn().expect("failed to generate the ro tx");
-	let table = ro_tx.open_table(Some(BENCHMARK_TABLES[1])).expect("failed to open table");
-	ro_tx.prime_for_permaopen(table);
-	let table = ro_tx.commit_and_rebind_open_dbs().unwrap().1.remove(0);
-
-	// Generate random data
-	let data: Vec<([u8; BENCHMARK_TABLES_KEY_SIZE[1]],[u8; BENCHMARK_TABLES_DATA_SIZE[1]])> = (0..BENCHMARK_TABLES_COMMITS[1])
-		.map(|_| {
-			let mut buf: Box<[u8; BENCHMARK_TABLES_DATA_SIZE[1]]> = Box::new([0u8; BENCHMARK_TABLES_DATA_SIZE[1]]); 
-			rng.fill_bytes(&mut *buf); 
-			(rng.gen(),*buf)
-		}).collect(); 
-	let mut data_iter = data.iter();
-
-	let data_cloned = data.clone();
-
-	// Measurement
-	let instant = Instant::now();
-
-	(0..(BENCHMARK_TABLES_COMMITS[1]/job_divide as usize)).for_each(|_| {
-
-		let rw_tx = db.begin_rw_txn().unwrap();
-					
-		(0..job_divide).for_each(|_| {
-
-			let data = data_iter.next().unwrap();
-			rw_tx.put(&table, data.0, data.1, WriteFlags::empty()).unwrap();
-		});
+	fn file_size(&self) -> u64 {
+		let file = File::open(self.path.join("mdbx.dat")).unwrap();
+		file.metadata().unwrap().len()
+	}
 
-		rw_tx.commit().unwrap();
-		pg.inc(job_divide);
-	});
+	fn used_size(&self) -> u64 {
+		let info = self.info();
+		(info.mi_last_pgno + 1) * info.mi_dxb_pagesize as u64
+	}
 
-	measurements.0.push(instant.elapsed().as_secs_f64());
+	fn stats(&self, tables: &[TableSpec]) -> DatabaseStats {
+		let info = self.info();
+
+		let ro_tx = self.begin_ro();
+		let tables = tables.iter()
+			.map(|spec| {
+				let table = ro_tx.open_table(Some(&spec.name)).unwrap();
+				let mut stat: MDBX_stat = unsafe { std::mem::zeroed() };
+				check(unsafe { mdbx_sys::mdbx_dbi_stat(ro_tx.txn(), table.dbi(), &mut stat, std::mem::size_of::<MDBX_stat>()) });
+				table_stats(&spec.name, &stat)
+			})
+			.collect();
+
+		// The GC is the table 0
+		let mut gc: MDBX_stat = unsafe { std::mem::zeroed() };
+		check(unsafe { mdbx_sys::mdbx_dbi_stat(ro_tx.txn(), 0, &mut gc, std::mem::size_of::<MDBX_stat>()) });
+
+		let (geo, ops) = (info.mi_geo, info.mi_pgop_stat);
+		DatabaseStats {
+			page_size: info.mi_dxb_pagesize,
+			geometry: MapGeometry { lower: geo.lower, upper: geo.upper, current: geo.current, grow: geo.grow, shrink: geo.shrink },
+			last_page: info.mi_last_pgno,
+			last_txn_id: info.mi_recent_txnid,
+			page_ops: Some(PageOps {
+				newly: ops.newly, cow: ops.cow, clone: ops.clone, split: ops.split, merge: ops.merge, spill: ops.spill,
+				unspill: ops.unspill, wops: ops.wops, prefault: ops.prefault, mincore: ops.mincore, msync: ops.msync, fsync: ops.fsync,
+			}),
+			tables,
+			gc: Some(table_stats("gc", &gc)),
+			hsr_calls: Some(self.slow_readers.calls.load(Ordering::Relaxed)),
+		}
+	}
+}
+
+impl<R: DatabaseKind> MdbxBackend<R> {
 
-	data_cloned
+	fn info(&self) -> MDBX_envinfo {
+		let mut info: MDBX_envinfo = unsafe { std::mem::zeroed() };
+		check(unsafe { mdbx_sys::mdbx_env_info_ex(self.db.ptr(), ptr::null(), &mut info, std::mem::size_of::<MDBX_envinfo>()) });
+		info
+	}
 }
 
-pub fn benchmark_read_large_table<R: DatabaseKind>(
-	pg: &ProgressBar,
-	db: &libmdbx::Database<R>, 
-	rng: &mut ThreadRng, 
-	job_divide: u64, 
-	mut data: Vec<([u8; BENCHMARK_TABLES_KEY_SIZE[1]],[u8; BENCHMARK_TABLES_DATA_SIZE[1]])>,
-	measurements: &mut (Vec<f64>, u64)) 
-{
-	// Open table
-	let ro_tx = db.begin_ro_txn().expect("failed to generate the ro tx");
-	let table = ro_tx.open_table(Some(BENCHMARK_TABLES[1])).expect("failed to open table");
-	ro_tx.prime_for_permaopen(table);
-	let table = ro_tx.commit_and_rebind_open_dbs().unwrap().1.remove(0);
-
-	// Generate random data
-	data.shuffle(rng);
-	let mut data_iter = data.iter();
-
-	// Measurement
-	let instant = Instant::now();
-
-	(0..(BENCHMARK_TABLES_COMMITS[1]/job_divide as usize)).for_each(|_| {
-
-		let ro_tx = db.begin_ro_txn().unwrap();
-					
-		(0..job_divide).for_each(|_| {
-
-			let data = data_iter.next().unwrap();
-			let _: [u8; BENCHMARK_TABLES_DATA_SIZE[1]] = ro_tx.get(&table, &data.0).unwrap().unwrap();
-		});
-		pg.inc(job_divide);
-	});
-
-	measurements.0.push(instant.elapsed().as_secs_f64());
+/// Write transaction begun on the calling thread.
+/// libmdbx begins and commits write transactions on its own thread with `mdbx_txn_commit`, which can't report
+/// the commit latency breakdown, and `mdbx_txn_commit_ex` must be called by the thread holding the write lock.
+pub struct RwTxn<'db> {
+	txn: *mut MDBX_txn,
+	_db: PhantomData<&'db ()>,
 }
 
-pub fn benchmark_read_small_table<R: DatabaseKind>(
-	pg: &ProgressBar,
-	db: &libmdbx::Database<R>, 
-	rng: &mut ThreadRng, 
-	job_divide: u64, 
-	mut data: Vec<([u8; BENCHMARK_TABLES_KEY_SIZE[0]],[u8; BENCHMARK_TABLES_DATA_SIZE[0]])>,
-	measurements: &mut (Vec<f64>, u64)) 
-{
-	// Open table
-	let ro_tx = db.begin_ro_txn().expect("failed to generate the ro tx");
-	let table = ro_tx.open_table(Some(BENCHMARK_TABLES[0])).expect("failed to open table");
-	ro_tx.prime_for_permaopen(table);
-	let table = ro_tx.commit_and_rebind_open_dbs().unwrap().1.remove(0);
-
-	// Generate random data
-	data.shuffle(rng);
-	let mut data_iter = data.iter();
-
-	// Measurement
-	let instant = Instant::now();
-
-	(0..(BENCHMARK_TABLES_COMMITS[0]/job_divide as usize)).for_each(|_| {
-
-		let ro_tx = db.begin_ro_txn().unwrap();
-					
-		(0..job_divide).for_each(|_| {
-
-			let data = data_iter.next().unwrap();
-			let _: Option<[u8; BENCHMARK_TABLES_DATA_SIZE[0]]> = ro_tx.get(&table, &data.0).unwrap();
-		});
-		pg.inc(job_divide);
-	});
-
-	measurements.0.push(instant.elapsed().as_secs_f64());
+impl Drop for RwTxn<'_> {
+	fn drop(&mut self) {
+		unsafe { mdbx_sys::mdbx_txn_abort(self.txn) };
+	}
 }
 
-pub fn benchmark_read_small_table_dup<R: DatabaseKind>(
-	pg: &ProgressBar,
-	db: &libmdbx::Database<R>, 
-	rng: &mut ThreadRng, 
-	job_divide: u64, 
-	mut data: Vec<([u8; BENCHMARK_TABLES_KEY_SIZE[0]+BENCHMARK_TABLES_DATA_SIZE[0]])>,
-	measurements: &mut (Vec<f64>, u64)) 
-{
-	// Open table
-	let ro_tx = db.begin_ro_txn().expect("failed to generate the ro tx");
-	let table = ro_tx.open_table(Some(BENCHMARK_TABLES[0])).expect("failed to open table");
-	ro_tx.prime_for_permaopen(table);
-	let table = ro_tx.commit_and_rebind_open_dbs().unwrap().1.remove(0);
-
-	// Generate random data
-	data.shuffle(rng);
-	let mut data_iter = data.iter();
-
-	// Measurement
-	let instant = Instant::now();
-
-	(0..(BENCHMARK_TABLES_COMMITS[0]/job_divide as usize)).for_each(|_| {
-
-		let ro_tx = db.begin_ro_txn().unwrap();
-		let mut cursor = ro_tx.cursor(&table).unwrap();
-					
-		(0..job_divide).for_each(|_| {
-
-			let data = data_iter.next().unwrap();
-			let cursor_ptr = cursor.cursor();
-			
-			unsafe { 
-				let mut data_iov = slice_to_val(Some(data));
-				let mut key_iov = slice_to_val(Some(&[0u8; 0]));
-				let data_ptr = data_iov.iov_base;
-				let key_ptr = key_iov.iov_base;
-				mdbx_sys::mdbx_cursor_get(cursor_ptr as *mut mdbx_sys::MDBX_cursor, &mut key_iov, &mut data_iov, 2) };
-			//let _: Option<[u8; BENCHMARK_TABLES_KEY_SIZE[0]+BENCHMARK_TABLES_DATA_SIZE[0]]> = cursor.get_both(&[0u8; 0], &data[..BENCHMARK_TABLES_KEY_SIZE[0]]).unwrap();
-		});
-		pg.inc(job_divide);
-	});
-
-	measurements.0.push(instant.elapsed().as_secs_f64());
+pub struct RwCursor<'txn> {
+	cursor: *mut MDBX_cursor,
+	_txn: PhantomData<&'txn ()>,
 }
 
-pub fn benchmark_put_small_table_dup<R: DatabaseKind>(
-	pg: &ProgressBar,
-	db: &libmdbx::Database<R>, 
-	rng: &mut ThreadRng, 
-	job_divide: u64, 
-	measurements: &mut (Vec<f64>, u64)) 
-	-> Vec<([u8; BENCHMARK_TABLES_KEY_SIZE[0]+BENCHMARK_TABLES_DATA_SIZE[0]])>
-{
-	// Generate random data
-	let data: Vec<[u8; BENCHMARK_TABLES_KEY_SIZE[0]+BENCHMARK_TABLES_DATA_SIZE[0]]> = (0..BENCHMARK_TABLES_COMMITS[0]).map(|_| {
-		let mut buf: [u8; BENCHMARK_TABLES_KEY_SIZE[0]+BENCHMARK_TABLES_DATA_SIZE[0]] = [0u8; BENCHMARK_TABLES_KEY_SIZE[0]+BENCHMARK_TABLES_DATA_SIZE[0]];
-		rng.fill_bytes(&mut buf);
-		buf
-	}).collect(); 
-	let mut data_iter = data.iter();
-
-	let data_returned = data.clone();
-
-	// Open table
-	let rw_tx = db.begin_rw_txn().expect("failed to generate the ro tx");
-	let table = rw_tx.open_table(Some(BENCHMARK_TABLES[0])).expect("failed to open table");
-	rw_tx.prime_for_permaopen(table);
-	let table = rw_tx.commit_and_rebind_open_dbs().unwrap().1.remove(0);
-
-	// Measurement
-	let instant = Instant::now();
-
-	(0..(BENCHMARK_TABLES_COMMITS[0]/job_divide as usize)).for_each(|_| {
-
-		// Open cursor
-		let rw_tx = db.begin_rw_txn().unwrap();
-		let mut cursor = rw_tx.cursor(&table).unwrap();
-					
-		(0..job_divide).for_each(|_| {
-
-			let data = data_iter.next().unwrap();
-			cursor.put(&[0u8; 0], data, WriteFlags::empty()).unwrap();
-		});
+impl Drop for RwCursor<'_> {
+	fn drop(&mut self) {
+		unsafe { mdbx_sys::mdbx_cursor_close(self.cursor) };
+	}
+}
 
-		rw_tx.commit().unwrap();
-		pg.inc(job_divide);
-	});
+fn val(data: &[u8]) -> MDBX_val {
+	MDBX_val { iov_base: data.as_ptr() as *mut _, iov_len: data.len() }
+}
 
-	measurements.0.push(instant.elapsed().as_secs_f64());
-	data_returned
+fn table_stats(name: &str, stat: &MDBX_stat) -> TableStats {
+	TableStats {
+		table: name.to_string(),
+		depth: stat.ms_depth,
+		branch_pages: stat.ms_branch_pages,
+		leaf_pages: stat.ms_leaf_pages,
+		overflow_pages: stat.ms_overflow_pages,
+		entries: stat.ms_entries,
+	}
 }
 
+/// Handle-Slow-Readers callback, called when the GC can't reclaim pages still visible to a reader and the file would grow.
+/// The held reader is told to end its transaction on its own thread, which releases its slot like any abort, then MDBX is told to retry.
+/// Other laggards are short read transactions, MDBX calls back until they end
+unsafe extern "C" fn oust_laggard(env: *const MDBX_env, _txn: *const MDBX_txn, _pid: mdbx_pid_t, _tid: mdbx_tid_t, laggard: u64, _gap: u32, _space: usize, retry: c_int) -> c_int {
+	if retry < 0 {
+		return 0;
+	}
+	let slow_readers = unsafe { &*(mdbx_sys::mdbx_env_get_userctx(env) as *const SlowReaders) };
+	slow_readers.calls.fetch_add(1, Ordering::Relaxed);
+
+	let held = slow_readers.held.lock().unwrap().take_if(|held| held.txn_id == laggard);
+	if let Some(held) = held {
+		// Both fail once the reader thread is gone, and its transaction with it
+		let _ = held.oust.send(());
+		let _ = held.ended.recv();
+	}
+	0
+}
 
-unsafe fn slice_to_val(slice: Option<&[u8]>) -> mdbx_sys::MDBX_val {
-	match slice {
-	    Some(slice) => mdbx_sys::MDBX_val {
-		iov_len: slice.len(),
-		iov_base: slice.as_ptr() as *mut std::ffi::c_void,
-	    },
-	    None => mdbx_sys::MDBX_val {
-		iov_len: 0,
-		iov_base: ptr::null_mut(),
-	    },
+fn check(rc: c_int) {
+	if rc != mdbx_sys::MDBX_SUCCESS {
+		panic!("{}", Error::from_err_code(rc));
 	}
-    }
\ No newline at end of file
+}
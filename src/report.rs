@@ -0,0 +1,838 @@
+use std::{path::{Path, PathBuf}, fs::File, io::Write, sync::{Arc, Mutex}};
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::{scenario::{ConfigSpec, Workload, MapKind, SyncKind, UpdateSize, DeleteOrder}, stats::{Summary, Latencies, LatencySummary, CommitBreakdown}, resources::ResourceUsage};
+
+/// Version of the report layout, bumped on every incompatible change.
+/// Reports without one are the legacy tuple reports, converted when loaded
+pub const SCHEMA_VERSION: u32 = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Report {
+	pub schema_version: u32,
+	/// Name of the scenario that produced the report
+	pub scenario: String,
+	/// Seed of the data generation, unknown for legacy reports
+	#[serde(default)]
+	pub seed: Option<u64>,
+	/// Factor applied to the entry count of every table
+	#[serde(default = "default_scale")]
+	pub scale: f64,
+	/// Set while the benchmark is running and when it was interrupted
+	#[serde(default)]
+	pub incomplete: bool,
+	pub benchmarks: Vec<BenchmarkReport>,
+	/// Put throughput of the configurations only differing by their batch size, set once the benchmarks are done
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub batch_sweeps: Vec<BatchSweep>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BenchmarkReport {
+	pub engine: String,
+	pub name: String,
+	pub configurations: Vec<ConfigReport>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigReport {
+	pub config: ConfigSpec,
+	pub iterations: Vec<IterationReport>,
+	/// Aggregates over the iterations, for every table and phase
+	#[serde(default)]
+	pub summary: Vec<PhaseSummary>,
+	/// Latency percentiles of the write transactions of every phase
+	#[serde(default)]
+	pub write_latency: Vec<PhaseLatency>,
+	/// Latency percentiles of the lookups of the reader threads of the mixed workload
+	#[serde(default)]
+	pub read_latency: LatencySummary,
+	/// Bytes the process wrote to the storage layer per byte of keys and values put, from `/proc/self/io`. Only on Linux.
+	/// The kernel flusher threads writing back the dirty pages of a `WriteMap` map aren't counted, so it is underestimated with `WriteMap`
+	#[serde(default)]
+	pub write_amplification: Option<f64>,
+	/// Time spent in each stage of the commits, summed over every phase. Only reported by MDBX
+	#[serde(default)]
+	pub commit_breakdown: Option<CommitBreakdown>,
+	/// Rounds of the crash-consistency verification, only with `--crash`
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub crash: Vec<CrashRound>,
+	/// Histograms the latency percentiles are computed from
+	#[serde(skip)]
+	pub latencies: Latencies,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhaseLatency {
+	pub phase: Phase,
+	/// Latency percentiles of every commit
+	pub commit: LatencySummary,
+	/// Latency percentiles of the writes of every transaction, without the commit
+	pub batch: LatencySummary,
+}
+
+/// The put workload of a child process killed with SIGKILL, and what the database holds once reopened
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct CrashRound {
+	pub round: u64,
+	/// Transactions the child reported as committed before being killed
+	pub acknowledged: u64,
+	/// Transactions fully present after reopening, in commit order
+	pub recovered: u64,
+	/// Acknowledged transactions missing after reopening
+	pub lost: u64,
+	/// Transactions with only part of their entries present, or present after a missing one
+	pub partial: u64,
+	/// Time to reopen the environment after the crash, tables excluded
+	pub recovery_seconds: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IterationReport {
+	pub iteration: u64,
+	/// Length of the data file, MDBX grows it by steps while LMDB with `WriteMap` sizes it to the whole map
+	pub db_file_size: u64,
+	/// Bytes of the pages in use, comparable between engines. Unknown for legacy reports
+	#[serde(default)]
+	pub db_used_size: Option<u64>,
+	pub tables: Vec<TableMeasurement>,
+	/// Statistics of the database at the end of the iteration
+	#[serde(default)]
+	pub stats: Option<DatabaseStats>,
+	/// File & GC size sampled during the overwrites of the long-lived read workload
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub growth: Vec<GrowthSample>,
+	/// Database before & after the deletes of the delete workload
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub prune: Option<PruneReport>,
+	/// Reorganizations of the reorg workload
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub reorg: Option<ReorgReport>,
+}
+
+/// Put throughput of the configurations of a benchmark only differing by their batch size
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchSweep {
+	pub engine: String,
+	pub benchmark: String,
+	pub kind: MapKind,
+	pub sync_mode: SyncKind,
+	/// Entry counts in increasing order, then byte limits
+	pub points: Vec<SweepPoint>,
+	/// Smallest `job_divide` reaching 90% of the best throughput of the entry counts
+	pub knee: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SweepPoint {
+	pub job_divide: u64,
+	pub batch_bytes: Option<usize>,
+	pub commits: u64,
+	pub ops_per_sec: f64,
+	pub mb_per_sec: f64,
+}
+
+/// Smallest `job_divide` reaching 90% of the best throughput of the entry counts, `points` being sorted by increasing entry count
+fn knee(points: &[SweepPoint]) -> Option<u64> {
+	let entry_counts = points.iter().filter(|point| point.batch_bytes.is_none());
+	let best = entry_counts.clone().map(|point| point.ops_per_sec).fold(0.0, f64::max);
+	entry_counts.clone().find(|point| point.ops_per_sec >= best * 0.9).map(|point| point.job_divide)
+}
+
+/// Transactions popping the top `depth` blocks and applying other ones, one of each per reorganization
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReorgReport {
+	pub depth: usize,
+	pub pop_seconds: Vec<f64>,
+	pub reapply_seconds: Vec<f64>,
+	/// File size after the import, before the first reorganization
+	pub file_size_before: u64,
+	pub file_size_after: u64,
+}
+
+/// Database after the puts, after the deletes and after inserting the deleted entries again
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PruneReport {
+	pub before_delete: PruneSnapshot,
+	pub after_delete: PruneSnapshot,
+	pub after_reinsert: PruneSnapshot,
+	/// Fraction of the pages the reinserted entries needed that came from the freed ones instead of the end of the file
+	pub reused_pages: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PruneSnapshot {
+	pub file_size: u64,
+	pub stats: DatabaseStats,
+}
+
+impl PruneReport {
+
+	pub fn new(before_delete: PruneSnapshot, after_delete: PruneSnapshot, after_reinsert: PruneSnapshot) -> PruneReport {
+		let table_pages = |snapshot: &PruneSnapshot| snapshot.stats.tables.iter().map(|t| t.branch_pages + t.leaf_pages + t.overflow_pages).sum::<u64>();
+		let needed = table_pages(&after_reinsert).saturating_sub(table_pages(&after_delete));
+		let grown = after_reinsert.stats.last_page.saturating_sub(after_delete.stats.last_page);
+		let reused_pages = if needed > 0 { 1.0 - (grown as f64 / needed as f64).min(1.0) } else { 1.0 };
+		PruneReport { before_delete, after_delete, after_reinsert, reused_pages }
+	}
+}
+
+/// Size of the database while a read transaction is held open, or after it was closed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GrowthSample {
+	/// Since the start of the overwrites
+	pub seconds: f64,
+	pub held: bool,
+	pub file_size: u64,
+	pub gc: Option<TableStats>,
+}
+
+/// Time spent in one phase on one table
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TableMeasurement {
+	pub table: String,
+	pub phase: Phase,
+	pub seconds: f64,
+	/// Number of key/value pairs written or read
+	pub ops: u64,
+	/// Bytes of keys and values written or read
+	pub bytes: u64,
+	/// Process resources used during the phase
+	#[serde(default)]
+	pub resources: Option<ResourceUsage>,
+	/// Reader threads of the read scaling workload
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub threads: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DatabaseStats {
+	pub page_size: u32,
+	pub geometry: MapGeometry,
+	pub last_page: u64,
+	pub last_txn_id: u64,
+	/// Page operations since the database was opened. Only reported by MDBX
+	pub page_ops: Option<PageOps>,
+	pub tables: Vec<TableStats>,
+	/// Tree of the garbage collection, the pages freed by committed transactions
+	#[serde(default)]
+	pub gc: Option<TableStats>,
+	/// Calls of the Handle-Slow-Readers callback since the database was opened. Only reported by MDBX
+	#[serde(default)]
+	pub hsr_calls: Option<u64>,
+}
+
+/// Bounds and steps of the memory map, in bytes. LMDB only has a fixed map size, reported as `current` and `upper`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct MapGeometry {
+	pub lower: u64,
+	pub upper: u64,
+	pub current: u64,
+	pub grow: u64,
+	pub shrink: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct PageOps {
+	pub newly: u64,
+	pub cow: u64,
+	pub clone: u64,
+	pub split: u64,
+	pub merge: u64,
+	pub spill: u64,
+	pub unspill: u64,
+	pub wops: u64,
+	pub prefault: u64,
+	pub mincore: u64,
+	pub msync: u64,
+	pub fsync: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TableStats {
+	pub table: String,
+	pub depth: u32,
+	pub branch_pages: u64,
+	pub leaf_pages: u64,
+	pub overflow_pages: u64,
+	pub entries: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhaseSummary {
+	pub table: String,
+	pub phase: Phase,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub threads: Option<usize>,
+	pub seconds: Summary,
+	pub ops_per_sec: f64,
+	/// Throughput of keys and values, in 10^6 bytes per second
+	pub mb_per_sec: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+	Put,
+	Read,
+	/// Lookups of the reader threads of the mixed workload, over the whole put phase
+	#[serde(rename = "concurrent_read")]
+	ConcurrentRead,
+	/// New values for every key
+	Overwrite,
+	/// Removal of `delete_fraction` of the entries
+	Delete,
+	/// Entries removed by the delete phase inserted again
+	Reinsert,
+	/// Blocks of the block import workload, written to every table
+	Import,
+	/// Top blocks removed from every table by a reorganization
+	Pop,
+	/// Blocks of the other chain applied by a reorganization
+	Reapply,
+	/// New values for every key while a read transaction opened before is still running
+	#[serde(rename = "overwrite_held")]
+	OverwriteHeld,
+}
+
+impl Report {
+
+	pub fn new(scenario: &str, seed: Option<u64>, scale: f64) -> Report {
+		Report { schema_version: SCHEMA_VERSION, scenario: scenario.to_string(), seed, scale, incomplete: true, benchmarks: Vec::new(), batch_sweeps: Vec::new() }
+	}
+
+	/// Write the report to a temporary file and rename it over `path`, so `path` always holds a whole report
+	pub fn save(&self, path: &Path) {
+		let tmp_path = path.with_extension("json.tmp");
+		let mut file = File::create(&tmp_path).expect("Can't create report");
+		file.write_all(serde_json::to_string(self).unwrap().as_bytes()).expect("Failed to write report");
+		file.sync_all().expect("Failed to write report");
+		std::fs::rename(&tmp_path, path).expect("Failed to write report");
+	}
+
+	/// Print a comparison table of every configuration
+	pub fn print_summary(&self) {
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).all(|config| config.iterations.is_empty()) {
+			return self.print_crash_summary();
+		}
+
+		println!("\n{:<6} {:<40} {:<16} {:<14} {:>10} {:>10} {:>10} {:>10} {:>6} {:>12} {:>10}", "Engine", "Configuration", "Table", "Phase", "Mean (s)", "Median", "Min", "Max", "CV %", "ops/s", "MB/s");
+		for benchmark in &self.benchmarks {
+			for config in &benchmark.configurations {
+				for summary in config.summary.iter().filter(|summary| summary.threads.is_none()) {
+					let seconds = &summary.seconds;
+					println!("{:<6} {:<40} {:<16} {:<14} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>6.1} {:>12.0} {:>10.1}",
+						benchmark.engine, config.config.name, summary.table, format!("{:?}", summary.phase),
+						seconds.mean, seconds.median, seconds.min, seconds.max, seconds.cv * 100.0, summary.ops_per_sec, summary.mb_per_sec);
+				}
+			}
+		}
+
+		println!("\n{:<6} {:<40} {:<14} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}", "Engine", "Configuration", "Phase", "Commits", "p50 (ms)", "p90", "p99", "p99.9", "Max", "Write amp");
+		for benchmark in &self.benchmarks {
+			for config in &benchmark.configurations {
+				for PhaseLatency { phase, commit: latency, .. } in config.write_latency.iter().filter(|latency| latency.commit.count > 0) {
+					let ms = |seconds: f64| seconds * 1e3;
+					// Writeback of the mmap by the kernel isn't attributed to the process
+					let unreliable = if config.config.kind == MapKind::WriteMap { "*" } else { "" };
+					// Only measured over the put phases
+					let write_amplification = config.write_amplification.filter(|_| *phase == Phase::Put).map_or("-".to_string(), |wa| format!("{wa:.2}{unreliable}"));
+					println!("{:<6} {:<40} {:<14} {:>10} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10}",
+						benchmark.engine, config.config.name, format!("{phase:?}"), latency.count, ms(latency.p50), ms(latency.p90), ms(latency.p99), ms(latency.p999), ms(latency.max), write_amplification);
+				}
+			}
+		}
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).any(|config| config.config.kind == MapKind::WriteMap && config.write_amplification.is_some()) {
+			println!("Write amp counts the bytes written by the benchmark process. * WriteMap pages written back by the kernel flusher threads are missed, so it is a lower bound");
+		}
+
+		println!("\nMean commit breakdown (ms)\n{:<6} {:<40} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}", "Engine", "Configuration", "Prepare", "GC wall", "GC cpu", "Audit", "Write", "Sync", "Ending", "Whole");
+		for benchmark in &self.benchmarks {
+			for config in &benchmark.configurations {
+				let Some(breakdown) = &config.commit_breakdown else { continue };
+				let ms = |seconds: f64| seconds * 1e3 / breakdown.commits as f64;
+				println!("{:<6} {:<40} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3}",
+					benchmark.engine, config.config.name, ms(breakdown.preparation), ms(breakdown.gc_wallclock), ms(breakdown.gc_cputime),
+					ms(breakdown.audit), ms(breakdown.write), ms(breakdown.sync), ms(breakdown.ending), ms(breakdown.whole));
+			}
+		}
+
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).any(|config| config.read_latency.count > 0) {
+			println!("\nConcurrent reads\n{:<6} {:<40} {:>8} {:>14} {:>14} {:>10} {:>10} {:>10} {:>10} {:>10}", "Engine", "Configuration", "Readers", "Writer ops/s", "Reader ops/s", "p50 (us)", "p90", "p99", "p99.9", "Max");
+		for benchmark in &self.benchmarks {
+			for config in benchmark.configurations.iter().filter(|config| config.read_latency.count > 0) {
+				let us = |seconds: f64| seconds * 1e6;
+				let ops_per_sec = |phase: Phase| {
+					let measurements = config.iterations.iter().flat_map(|iteration| &iteration.tables).filter(|m| m.phase == phase);
+					let (ops, seconds) = measurements.fold((0, 0.0), |(ops, seconds), m| (ops + m.ops, seconds + m.seconds));
+					ops as f64 / seconds
+				};
+				// Reader tables share the same window, count it once per iteration
+				let reader_tables = config.iterations.first().map_or(1, |iteration| iteration.tables.iter().filter(|m| m.phase == Phase::ConcurrentRead).count());
+				let latency = &config.read_latency;
+				println!("{:<6} {:<40} {:>8} {:>14.0} {:>14.0} {:>10.1} {:>10.1} {:>10.1} {:>10.1} {:>10.1}",
+					benchmark.engine, config.config.name, config.config.readers, ops_per_sec(Phase::Put), ops_per_sec(Phase::ConcurrentRead) * reader_tables as f64,
+					us(latency.p50), us(latency.p90), us(latency.p99), us(latency.p999), us(latency.max));
+				}
+			}
+		}
+
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).flat_map(|config| &config.summary).any(|summary| summary.threads.is_some()) {
+			println!("\nRead scaling\n{:<6} {:<40} {:<10} {:<16} {:>8} {:>12} {:>10} {:>8}", "Engine", "Configuration", "Map", "Table", "Threads", "ops/s", "MB/s", "Speedup");
+			for benchmark in &self.benchmarks {
+				for config in &benchmark.configurations {
+					for summary in config.summary.iter().filter(|summary| summary.threads.is_some()) {
+						let single = config.summary.iter().find(|single| single.table == summary.table && single.threads == Some(1)).map_or(f64::NAN, |single| single.ops_per_sec);
+						println!("{:<6} {:<40} {:<10} {:<16} {:>8} {:>12.0} {:>10.1} {:>8.2}",
+							benchmark.engine, config.config.name, format!("{:?}", config.config.kind), summary.table, summary.threads.unwrap(), summary.ops_per_sec, summary.mb_per_sec, summary.ops_per_sec / single);
+					}
+				}
+			}
+		}
+
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).flat_map(|config| &config.iterations).any(|iteration| !iteration.growth.is_empty()) {
+			println!("\nLong-lived reader\n{:<6} {:<40} {:>5} {:>14} {:>14} {:>14} {:>14} {:>12} {:>10}", "Engine", "Configuration", "HSR", "Held ops/s", "After ops/s", "Held file +MB", "After file +MB", "Max GC pages", "HSR calls");
+			for benchmark in &self.benchmarks {
+				for config in benchmark.configurations.iter().filter(|config| config.iterations.iter().any(|iteration| !iteration.growth.is_empty())) {
+					let ops_per_sec = |phase: Phase| {
+						let summaries = config.summary.iter().filter(|summary| summary.phase == phase);
+						let (ops, seconds) = summaries.fold((0.0, 0.0), |(ops, seconds), summary| (ops + summary.ops_per_sec * summary.seconds.mean, seconds + summary.seconds.mean));
+						ops / seconds
+					};
+					// File growth from the first sample to the largest one, averaged over the iterations
+					let growth = |held: bool| {
+						let growths: Vec<f64> = config.iterations.iter().filter_map(|iteration| {
+							let mut sizes = iteration.growth.iter().filter(|sample| sample.held == held).map(|sample| sample.file_size);
+							let first = sizes.next()?;
+							Some(sizes.max().unwrap_or(first).saturating_sub(first) as f64 / 1e6)
+						}).collect();
+						Summary::of(&growths).mean
+					};
+					let gc_pages = config.iterations.iter().flat_map(|iteration| &iteration.growth)
+						.filter_map(|sample| sample.gc.as_ref())
+						.map(|gc| gc.branch_pages + gc.leaf_pages + gc.overflow_pages)
+						.max().unwrap_or(0);
+					let hsr_calls: Option<u64> = config.iterations.iter().map(|iteration| iteration.stats.as_ref().and_then(|stats| stats.hsr_calls)).sum();
+					println!("{:<6} {:<40} {:>5} {:>14.0} {:>14.0} {:>14.1} {:>14.1} {:>12} {:>10}",
+						benchmark.engine, config.config.name, config.config.hsr, ops_per_sec(Phase::OverwriteHeld), ops_per_sec(Phase::Overwrite),
+						growth(true), growth(false), gc_pages, hsr_calls.map_or("-".to_string(), |calls| calls.to_string()));
+				}
+			}
+		}
+
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).flat_map(|config| &config.iterations).any(|iteration| iteration.prune.is_some()) {
+			println!("\nPruning\n{:<6} {:<40} {:<8} {:>8} {:>14} {:>14} {:>10} {:>13} {:>15} {:>12} {:>8}", "Engine", "Configuration", "Order", "Fraction", "Delete ops/s", "Reinsert ops/s", "File MB", "Delete MB", "Reinsert MB", "GC pages", "Reused");
+			for benchmark in &self.benchmarks {
+				for config in benchmark.configurations.iter().filter(|config| config.iterations.iter().any(|iteration| iteration.prune.is_some())) {
+					let ops_per_sec = |phase: Phase| {
+						let summaries = config.summary.iter().filter(|summary| summary.phase == phase);
+						let (ops, seconds) = summaries.fold((0.0, 0.0), |(ops, seconds), summary| (ops + summary.ops_per_sec * summary.seconds.mean, seconds + summary.seconds.mean));
+						ops / seconds
+					};
+					// Averaged over the iterations
+					let prunes: Vec<&PruneReport> = config.iterations.iter().filter_map(|iteration| iteration.prune.as_ref()).collect();
+					let mean = |value: &dyn Fn(&PruneReport) -> f64| Summary::of(&prunes.iter().map(|prune| value(prune)).collect::<Vec<f64>>()).mean;
+					let file_size = |snapshot: &PruneSnapshot| snapshot.file_size as f64 / 1e6;
+					let gc_pages = |snapshot: &PruneSnapshot| snapshot.stats.gc.as_ref().map_or(0.0, |gc| (gc.branch_pages + gc.leaf_pages + gc.overflow_pages) as f64);
+					println!("{:<6} {:<40} {:<8} {:>8.3} {:>14.0} {:>14.0} {:>10.1} {:>+13.1} {:>+15.1} {:>12.0} {:>7.1}%",
+						benchmark.engine, config.config.name, format!("{:?}", config.config.delete_order).to_lowercase(), config.config.delete_fraction,
+						ops_per_sec(Phase::Delete), ops_per_sec(Phase::Reinsert),
+						mean(&|prune| file_size(&prune.before_delete)),
+						mean(&|prune| file_size(&prune.after_delete) - file_size(&prune.before_delete)),
+						mean(&|prune| file_size(&prune.after_reinsert) - file_size(&prune.after_delete)),
+						mean(&|prune| gc_pages(&prune.after_delete)), mean(&|prune| prune.reused_pages * 100.0));
+				}
+			}
+		}
+
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).any(|config| config.config.sets_geometry() && !config.iterations.is_empty()) {
+			println!("\nGeometry\n{:<6} {:<40} {:>8} {:>10} {:>10} {:>10} {:>12} {:>12} {:>10} {:>14}", "Engine", "Configuration", "Page", "Grow MB", "Lower MB", "Shrink MB", "Put ops/s", "Read ops/s", "File MB", "Overflow pages");
+			for benchmark in &self.benchmarks {
+				for config in benchmark.configurations.iter().filter(|config| config.config.sets_geometry()) {
+					// Geometry the engine actually used, from the stats of the last iteration
+					let Some(stats) = config.iterations.last().and_then(|iteration| iteration.stats.as_ref()) else { continue };
+					let ops_per_sec = |phase: Phase| {
+						let summaries = config.summary.iter().filter(|summary| summary.phase == phase && summary.threads.is_none());
+						let (ops, seconds) = summaries.fold((0.0, 0.0), |(ops, seconds), summary| (ops + summary.ops_per_sec * summary.seconds.mean, seconds + summary.seconds.mean));
+						if seconds > 0.0 { format!("{:.0}", ops / seconds) } else { "-".to_string() }
+					};
+					let file_size = Summary::of(&config.iterations.iter().map(|iteration| iteration.db_file_size as f64 / 1e6).collect::<Vec<f64>>()).mean;
+					let overflow_pages: u64 = stats.tables.iter().map(|table| table.overflow_pages).sum();
+					let mb = |bytes: u64| bytes as f64 / 1024f64.powi(2);
+					println!("{:<6} {:<40} {:>8} {:>10.0} {:>10.0} {:>10.0} {:>12} {:>12} {:>10.1} {:>14}",
+						benchmark.engine, config.config.name, stats.page_size, mb(stats.geometry.grow), mb(stats.geometry.lower), mb(stats.geometry.shrink),
+						ops_per_sec(Phase::Put), ops_per_sec(Phase::Read), file_size, overflow_pages);
+				}
+			}
+		}
+
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).any(|config| config.config.workload == Workload::BlockImport && !config.iterations.is_empty()) {
+			println!("\nBlock import\n{:<6} {:<40} {:>12} {:>10} {:>10} {:>14} {:>10} {:>10} {:>14}", "Engine", "Configuration", "Blocks/txn", "Blocks/s", "MB/s", "Commit p99 ms", "File MB", "Used MB", "Used KB/block");
+			for benchmark in &self.benchmarks {
+				for config in benchmark.configurations.iter().filter(|config| config.config.workload == Workload::BlockImport && !config.iterations.is_empty()) {
+					let Some(summary) = config.summary.iter().find(|summary| summary.phase == Phase::Import) else { continue };
+					let blocks = Summary::of(&config.iterations.iter().flat_map(|iteration| &iteration.tables).filter(|table| table.phase == Phase::Import).map(|table| table.ops as f64).collect::<Vec<f64>>()).mean;
+					let file_size = Summary::of(&config.iterations.iter().map(|iteration| iteration.db_file_size as f64).collect::<Vec<f64>>()).mean;
+					let used_size = Summary::of(&config.iterations.iter().filter_map(|iteration| iteration.db_used_size).map(|size| size as f64).collect::<Vec<f64>>()).mean;
+					println!("{:<6} {:<40} {:>12} {:>10.0} {:>10.1} {:>14.3} {:>10.1} {:>10.1} {:>14.2}",
+						benchmark.engine, config.config.name, config.config.job_divide, summary.ops_per_sec, summary.mb_per_sec,
+						config.commit_latency(Phase::Import).p99 * 1e3, file_size / 1e6, used_size / 1e6, used_size / blocks / 1e3);
+				}
+			}
+		}
+
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).flat_map(|config| &config.iterations).any(|iteration| iteration.reorg.is_some()) {
+			println!("\nReorganizations\n{:<6} {:<40} {:>6} {:>8} {:>14} {:>14} {:>16} {:>16} {:>10}", "Engine", "Configuration", "Depth", "Reorgs", "Pop p50 ms", "Pop max ms", "Reapply p50 ms", "Reapply max ms", "File +MB");
+			for benchmark in &self.benchmarks {
+				for config in benchmark.configurations.iter().filter(|config| config.iterations.iter().any(|iteration| iteration.reorg.is_some())) {
+					let reorgs: Vec<&ReorgReport> = config.iterations.iter().filter_map(|iteration| iteration.reorg.as_ref()).collect();
+					let pops = Summary::of(&reorgs.iter().flat_map(|reorg| &reorg.pop_seconds).map(|seconds| seconds * 1e3).collect::<Vec<f64>>());
+					let reapplies = Summary::of(&reorgs.iter().flat_map(|reorg| &reorg.reapply_seconds).map(|seconds| seconds * 1e3).collect::<Vec<f64>>());
+					let growth = Summary::of(&reorgs.iter().map(|reorg| (reorg.file_size_after as f64 - reorg.file_size_before as f64) / 1e6).collect::<Vec<f64>>()).mean;
+					println!("{:<6} {:<40} {:>6} {:>8} {:>14.3} {:>14.3} {:>16.3} {:>16.3} {:>+10.1}",
+						benchmark.engine, config.config.name, reorgs[0].depth, reorgs.iter().map(|reorg| reorg.pop_seconds.len()).sum::<usize>(),
+						pops.median, pops.max, reapplies.median, reapplies.max, growth);
+				}
+			}
+		}
+
+		self.print_batch_sweep();
+		self.print_crash_summary();
+	}
+
+	/// Group the put configurations of a benchmark only differing by their batch size, when there are at least 3 of them
+	pub fn summarize_batch_sweeps(&mut self) {
+		self.batch_sweeps.clear();
+		for benchmark in &self.benchmarks {
+			let mut sweeps: Vec<(Value, Vec<&ConfigReport>)> = Vec::new();
+			for config in benchmark.configurations.iter().filter(|config| config.config.workload == Workload::Put && !config.iterations.is_empty()) {
+				// Every setting but the name & batch size
+				let mut key = serde_json::to_value(&config.config).unwrap();
+				["name", "job_divide", "batch_bytes"].iter().for_each(|field| { key.as_object_mut().unwrap().remove(*field); });
+				match sweeps.iter_mut().find(|(sweep, _)| *sweep == key) {
+					Some((_, configs)) => configs.push(config),
+					None => sweeps.push((key, vec![config])),
+				}
+			}
+
+			for (_, mut configs) in sweeps.into_iter().filter(|(_, configs)| configs.len() >= 3) {
+				let (kind, sync_mode) = (configs[0].config.kind, configs[0].config.sync_mode);
+				// Entry counts in increasing order, then byte limits
+				configs.sort_by_key(|config| (config.config.batch_bytes.is_some(), config.config.batch_bytes.unwrap_or(0), config.config.job_divide));
+				let points: Vec<SweepPoint> = configs.into_iter().map(|config| {
+					let summaries = config.summary.iter().filter(|summary| summary.phase == Phase::Put);
+					let (ops, bytes, seconds) = summaries.fold((0.0, 0.0, 0.0), |(ops, bytes, seconds), summary|
+						(ops + summary.ops_per_sec * summary.seconds.mean, bytes + summary.mb_per_sec * summary.seconds.mean, seconds + summary.seconds.mean));
+					SweepPoint { job_divide: config.config.job_divide, batch_bytes: config.config.batch_bytes, commits: config.commit_latency(Phase::Put).count, ops_per_sec: ops / seconds, mb_per_sec: bytes / seconds }
+				}).collect();
+				let knee = knee(&points);
+				self.batch_sweeps.push(BatchSweep { engine: benchmark.engine.clone(), benchmark: benchmark.name.clone(), kind, sync_mode, points, knee });
+			}
+		}
+	}
+
+	fn print_batch_sweep(&self) {
+		if self.batch_sweeps.is_empty() {
+			return;
+		}
+		println!("\nBatch size sweep\n{:<6} {:<24} {:<12} {:<14} {:>10} {:>10} {:>12} {:>10} {:>8}", "Engine", "Benchmark", "Kind", "Sync mode", "Batch", "Commits", "ops/s", "MB/s", "% best");
+		for sweep in &self.batch_sweeps {
+			let best = sweep.points.iter().map(|point| point.ops_per_sec).fold(0.0, f64::max);
+			for point in &sweep.points {
+				let batch = match point.batch_bytes {
+					Some(bytes) => format!("{} MiB", bytes / 1024usize.pow(2)),
+					None => point.job_divide.to_string(),
+				};
+				let is_knee = point.batch_bytes.is_none() && sweep.knee == Some(point.job_divide);
+				println!("{:<6} {:<24} {:<12} {:<14} {:>10} {:>10} {:>12.0} {:>10.1} {:>7.1}%{}",
+					sweep.engine, sweep.benchmark, format!("{:?}", sweep.kind), format!("{:?}", sweep.sync_mode), batch, point.commits, point.ops_per_sec, point.mb_per_sec,
+					point.ops_per_sec / best * 100.0, if is_knee { " knee" } else { "" });
+			}
+		}
+	}
+
+	fn print_crash_summary(&self) {
+		if self.benchmarks.iter().flat_map(|benchmark| &benchmark.configurations).all(|config| config.crash.is_empty()) {
+			return;
+		}
+		println!("\nCrash consistency\n{:<6} {:<40} {:<14} {:>8} {:>12} {:>10} {:>10} {:>14}", "Engine", "Configuration", "Sync mode", "Rounds", "Acknowledged", "Lost", "Partial", "Recovery (ms)");
+		for benchmark in &self.benchmarks {
+			for config in benchmark.configurations.iter().filter(|config| !config.crash.is_empty()) {
+				let rounds = &config.crash;
+				let recovery: Vec<f64> = rounds.iter().map(|round| round.recovery_seconds * 1e3).collect();
+				println!("{:<6} {:<40} {:<14} {:>8} {:>12} {:>10} {:>10} {:>14.3}",
+					benchmark.engine, config.config.name, format!("{:?}", config.config.sync_mode), rounds.len(),
+					rounds.iter().map(|round| round.acknowledged).sum::<u64>(), rounds.iter().map(|round| round.lost).sum::<u64>(),
+					rounds.iter().map(|round| round.partial).sum::<u64>(), Summary::of(&recovery).mean);
+			}
+		}
+		println!("The killed process leaves its writes in the page cache, so this checks consistency only, not durability: no sync mode loses transactions here");
+	}
+
+	/// Load a report of any version, converting tuple based reports of the first releases
+	pub fn load(path: &Path) -> Report {
+		Report::parse(&std::fs::read_to_string(path).expect("Can't read report"))
+	}
+
+	fn parse(content: &str) -> Report {
+		// Interrupted legacy runs left several arrays concatenated in the file, the last one is the most complete
+		let value = serde_json::Deserializer::from_str(content)
+			.into_iter::<Value>()
+			.filter_map(Result::ok)
+			.last()
+			.expect("Report is not valid JSON");
+
+		match value.get("schema_version").and_then(Value::as_u64) {
+			Some(version) if version == SCHEMA_VERSION as u64 => serde_json::from_value(value).expect("Invalid report"),
+			Some(version) => panic!("Unsupported report schema version {version}, this build reads version {SCHEMA_VERSION}"),
+			None => match value {
+				Value::Array(benchmarks) => legacy::import(benchmarks),
+				_ => panic!("Report has no schema_version and is not a legacy report"),
+			},
+		}
+	}
+}
+
+impl ConfigReport {
+
+	pub fn new(config: ConfigSpec) -> ConfigReport {
+		ConfigReport { config, iterations: Vec::new(), summary: Vec::new(), write_amplification: None, write_latency: Vec::new(), read_latency: LatencySummary::default(), commit_breakdown: None, crash: Vec::new(), latencies: Latencies::default() }
+	}
+
+	/// Commit latency percentiles of `phase`, empty if it didn't run
+	pub fn commit_latency(&self, phase: Phase) -> LatencySummary {
+		self.write_latency.iter().find(|latency| latency.phase == phase).map_or(LatencySummary::default(), |latency| latency.commit)
+	}
+
+	/// Recompute the summary from the iterations
+	pub fn summarize(&mut self) {
+		self.write_latency = self.latencies.writes.iter()
+			.map(|writes| PhaseLatency { phase: writes.phase, commit: writes.commit.summary(), batch: writes.batch.summary() })
+			.collect();
+		self.read_latency = self.latencies.read.summary();
+		self.commit_breakdown = (self.latencies.breakdown.commits > 0).then_some(self.latencies.breakdown);
+
+		let puts: Vec<&TableMeasurement> = self.iterations.iter()
+			.flat_map(|iteration| iteration.tables.iter())
+			.filter(|m| m.phase == Phase::Put)
+			.collect();
+		let written: Option<u64> = puts.iter().map(|m| m.resources.and_then(|r| r.write_bytes)).sum();
+		let put_bytes: u64 = puts.iter().map(|m| m.bytes).sum();
+		self.write_amplification = written.filter(|_| put_bytes > 0).map(|written| written as f64 / put_bytes as f64);
+
+		self.summary.clear();
+		let Some(first) = self.iterations.first() else { return };
+
+		for measurement in &first.tables {
+			let measurements: Vec<&TableMeasurement> = self.iterations.iter()
+				.flat_map(|iteration| iteration.tables.iter())
+				.filter(|m| m.table == measurement.table && m.phase == measurement.phase && m.threads == measurement.threads)
+				.collect();
+
+			let seconds: Vec<f64> = measurements.iter().map(|m| m.seconds).collect();
+			let total_seconds: f64 = seconds.iter().sum();
+			let total_ops: u64 = measurements.iter().map(|m| m.ops).sum();
+			let total_bytes: u64 = measurements.iter().map(|m| m.bytes).sum();
+
+			self.summary.push(PhaseSummary {
+				table: measurement.table.clone(),
+				phase: measurement.phase,
+				threads: measurement.threads,
+				seconds: Summary::of(&seconds),
+				ops_per_sec: total_ops as f64 / total_seconds,
+				mb_per_sec: total_bytes as f64 / 1e6 / total_seconds,
+			});
+		}
+	}
+}
+
+fn default_scale() -> f64 {
+	1.0
+}
+
+/// Block SIGINT in every thread and wait for it in a dedicated one, which saves the report marked as incomplete and exits.
+/// Must be called before spawning any other thread.
+#[cfg(unix)]
+pub fn flush_on_sigint(report: Arc<Mutex<Report>>, path: PathBuf) {
+	let mut set: libc::sigset_t = unsafe { std::mem::zeroed() };
+	unsafe {
+		libc::sigemptyset(&mut set);
+		libc::sigaddset(&mut set, libc::SIGINT);
+		libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut());
+	}
+
+	std::thread::spawn(move || {
+		let mut signal = 0;
+		unsafe { libc::sigwait(&set, &mut signal) };
+
+		// A benchmark thread may have panicked while holding the lock, the report is still worth saving
+		let mut report = report.lock().unwrap_or_else(|err| err.into_inner());
+		report.incomplete = true;
+		report.save(&path);
+		eprintln!("\nBenchmark interrupted, partial report written to {}", path.display());
+		std::process::exit(130);
+	});
+}
+
+/// Reports written before `schema_version` existed:
+/// `[{ "name", "durations_size": [[[[[sim_blocks seconds, sim_blockheight seconds], file size], ...], config name], ...] }]`
+mod legacy {
+	use super::*;
+	use crate::JOB_DIVIDE;
+
+	/// Durations of both tables & database file size, for every iteration
+	type LegacyMeasurements = Vec<([f64; 2], u64)>;
+
+	#[derive(Deserialize)]
+	struct LegacyBenchmark {
+		name: String,
+		durations_size: Vec<(LegacyMeasurements, String)>,
+	}
+
+	/// Tables of the hardcoded workload, in the order of the duration slots
+	const TABLES: [(&str, u64, u64); 2] = [
+		("sim_blocks", 40000, 32+61440),
+		("sim_blockheight", 3000000, 8+32),
+	];
+
+	pub fn import(benchmarks: Vec<Value>) -> Report {
+		let mut report = Report::new("legacy", None, 1.0);
+		report.incomplete = false;
+
+		for benchmark in benchmarks {
+			let benchmark: LegacyBenchmark = serde_json::from_value(benchmark).expect("Invalid legacy report");
+
+			// Zero-key runs only have the sim_blockheight table, and put 0 in the first slot
+			let zero_key = benchmark.name.contains("ZeroKey");
+
+			let configurations = benchmark.durations_size.into_iter()
+				.map(|(iterations, name)| {
+					let config = config_from_name(&name);
+					// Legacy reports only have put & read workloads
+					let phase = match config.workload {
+						Workload::Read => Phase::Read,
+						_ => Phase::Put,
+					};
+					let iterations = iterations.into_iter().enumerate()
+						.map(|(iteration, (durations, db_file_size))| IterationReport {
+							iteration: iteration as u64,
+							db_file_size,
+							db_used_size: None,
+							tables: TABLES.iter().zip(durations)
+								.skip(zero_key as usize)
+								.map(|(&(table, ops, entry_size), seconds)| TableMeasurement { table: table.to_string(), phase, seconds, ops, bytes: ops * entry_size, resources: None, threads: None })
+								.collect(),
+							stats: None,
+							growth: Vec::new(),
+							prune: None,
+							reorg: None,
+						})
+						.collect();
+					let mut config = ConfigReport::new(config);
+					config.iterations = iterations;
+					config.summarize();
+					config
+				})
+				.collect();
+
+			report.benchmarks.push(BenchmarkReport { engine: "MDBX".to_string(), name: benchmark.name, configurations });
+		}
+
+		report.summarize_batch_sweeps();
+		report
+	}
+
+	/// Recover the configuration from names like "T K/P SM::SafeNoSync | WriteMap | J10K"
+	pub(super) fn config_from_name(name: &str) -> ConfigSpec {
+		let workload = if name.contains("Read") { Workload::Read } else { Workload::Put };
+		let kind = if name.contains("NoWriteMap") { MapKind::NoWriteMap } else { MapKind::WriteMap };
+		let sync_mode = match workload {
+			Workload::Read => SyncKind::UtterlyNoSync,
+			_ if name.contains("SafeNoSync") => SyncKind::SafeNoSync,
+			_ => SyncKind::Durable,
+		};
+		let extended = name.contains("J10K");
+		let job_divide = if extended { 10000 } else { JOB_DIVIDE };
+		ConfigSpec { name: name.to_string(), workload, kind, sync_mode, job_divide, batch_bytes: None, readers: 0, update: UpdateSize::Same, delete_fraction: 0.5, delete_order: DeleteOrder::Random, growth_step: None, lower_size: 0, shrink_threshold: None, page_size: None, reorg_depth: 1, hsr: false, extended }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn load_legacy_report() {
+		// An interrupted run left a first, shorter array before the complete one
+		let content = r#"[{"name":"T K/P","durations_size":[]}]
+			[{"name":"T K/P","durations_size":[
+				[[[[2.0,3.0],1000],[[4.0,5.0],2000]],"T K/P SM::SafeNoSync | NoWriteMap | J10K"],
+				[[[[1.0,1.0],2000]],"T K/P Read | WriteMap"]
+			]}]"#;
+		let report = Report::parse(content);
+
+		assert_eq!(report.schema_version, SCHEMA_VERSION);
+		assert_eq!(report.seed, None);
+		assert!(!report.incomplete);
+		let configs = &report.benchmarks[0].configurations;
+		assert_eq!(configs.len(), 2);
+
+		let put = &configs[0];
+		assert_eq!((put.config.workload, put.config.kind, put.config.sync_mode), (Workload::Put, MapKind::NoWriteMap, SyncKind::SafeNoSync));
+		assert_eq!(put.config.job_divide, 10000);
+		assert_eq!(put.iterations.len(), 2);
+		assert_eq!(put.iterations[1].db_file_size, 2000);
+		let blocks = &put.iterations[0].tables[0];
+		assert_eq!((blocks.table.as_str(), blocks.phase, blocks.seconds, blocks.ops), ("sim_blocks", Phase::Put, 2.0, 40000));
+		let summary = put.summary.iter().find(|summary| summary.table == "sim_blockheight").unwrap();
+		assert_eq!(summary.ops_per_sec, 2.0 * 3000000.0 / 8.0);
+
+		assert_eq!(configs[1].config.workload, Workload::Read);
+		assert_eq!(configs[1].iterations[0].tables[0].phase, Phase::Read);
+	}
+
+	#[test]
+	fn load_current_report() {
+		let mut report = Report::new("test", Some(7), 0.5);
+		let mut config = ConfigReport::new(legacy::config_from_name("T K/P SM::Durable | WriteMap"));
+		config.iterations.push(IterationReport {
+			iteration: 0,
+			db_file_size: 4096,
+			db_used_size: Some(4096),
+			tables: vec![TableMeasurement { table: "a".to_string(), phase: Phase::Put, seconds: 0.5, ops: 100, bytes: 4000, resources: None, threads: None }],
+			stats: None,
+			growth: Vec::new(),
+			prune: None,
+			reorg: None,
+		});
+		config.summarize();
+		report.benchmarks.push(BenchmarkReport { engine: "MDBX".to_string(), name: "T".to_string(), configurations: vec![config] });
+
+		let loaded = Report::parse(&serde_json::to_string(&report).unwrap());
+		assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&report).unwrap());
+		assert_eq!(loaded.benchmarks[0].configurations[0].summary[0].ops_per_sec, 200.0);
+	}
+
+	#[test]
+	fn knee_of_a_batch_sweep() {
+		let point = |job_divide, batch_bytes, ops_per_sec| SweepPoint { job_divide, batch_bytes, commits: 0, ops_per_sec, mb_per_sec: 0.0 };
+		let curve = |ops: &[(u64, f64)]| ops.iter().map(|&(job_divide, ops_per_sec)| point(job_divide, None, ops_per_sec)).collect::<Vec<_>>();
+
+		// 900 is the first entry count within 90% of the best one, the faster byte limit isn't an entry count
+		let mut points = curve(&[(1, 100.0), (10, 500.0), (100, 900.0), (1000, 1000.0), (10000, 950.0)]);
+		points.push(point(1000, Some(64 * 1024usize.pow(2)), 2000.0));
+		assert_eq!(knee(&points), Some(100));
+
+		assert_eq!(knee(&curve(&[(1, 1000.0), (10, 10.0), (100, 10.0)])), Some(1));
+		assert_eq!(knee(&curve(&[(1, 10.0), (10, 20.0), (100, 899.0), (1000, 1000.0)])), Some(1000));
+		assert_eq!(knee(&[point(1000, Some(1024), 10.0)]), None);
+	}
+
+	#[test]
+	#[should_panic(expected = "Unsupported report schema version")]
+	fn reject_other_schema_version() {
+		Report::parse(r#"{"schema_version":1,"scenario":"test","benchmarks":[]}"#);
+	}
+}
@@ -0,0 +1,103 @@
+use serde::{Serialize, Deserialize};
+
+/// What the OS did for the process during a phase
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct ResourceUsage {
+	/// Bytes fetched from the storage layer, from `/proc/self/io`. Only on Linux
+	pub read_bytes: Option<u64>,
+	/// Bytes sent to the storage layer, from `/proc/self/io`. Only on Linux
+	pub write_bytes: Option<u64>,
+	pub user_cpu: f64,
+	pub sys_cpu: f64,
+	pub major_faults: u64,
+	pub minor_faults: u64,
+	pub voluntary_switches: u64,
+	pub involuntary_switches: u64,
+	/// Peak resident set size of the process since it started, in bytes
+	pub peak_rss: u64,
+}
+
+impl ResourceUsage {
+
+	/// Nothing used yet, with the I/O counters of `/proc/self/io` at 0 so that `add` sums them
+	pub fn zero() -> ResourceUsage {
+		ResourceUsage { read_bytes: Some(0), write_bytes: Some(0), ..Default::default() }
+	}
+
+	pub fn add(&mut self, other: &ResourceUsage) {
+		let add = |a: Option<u64>, b: Option<u64>| a.zip(b).map(|(a, b)| a + b);
+		self.read_bytes = add(self.read_bytes, other.read_bytes);
+		self.write_bytes = add(self.write_bytes, other.write_bytes);
+		self.user_cpu += other.user_cpu;
+		self.sys_cpu += other.sys_cpu;
+		self.major_faults += other.major_faults;
+		self.minor_faults += other.minor_faults;
+		self.voluntary_switches += other.voluntary_switches;
+		self.involuntary_switches += other.involuntary_switches;
+		self.peak_rss = self.peak_rss.max(other.peak_rss);
+	}
+}
+
+/// Counters of the process at one point in time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sample {
+	io: Option<(u64, u64)>,
+	usage: ResourceUsage,
+}
+
+impl Sample {
+
+	pub fn now() -> Sample {
+		Sample { io: proc_io(), usage: rusage() }
+	}
+
+	/// Usage between `self` and now
+	pub fn elapsed(&self) -> ResourceUsage {
+		let now = Sample::now();
+		let io = self.io.zip(now.io).map(|((read, written), (read_now, written_now))| (read_now - read, written_now - written));
+		let (before, after) = (self.usage, now.usage);
+		ResourceUsage {
+			read_bytes: io.map(|io| io.0),
+			write_bytes: io.map(|io| io.1),
+			user_cpu: after.user_cpu - before.user_cpu,
+			sys_cpu: after.sys_cpu - before.sys_cpu,
+			major_faults: after.major_faults - before.major_faults,
+			minor_faults: after.minor_faults - before.minor_faults,
+			voluntary_switches: after.voluntary_switches - before.voluntary_switches,
+			involuntary_switches: after.involuntary_switches - before.involuntary_switches,
+			peak_rss: after.peak_rss,
+		}
+	}
+}
+
+/// `read_bytes` and `write_bytes` of `/proc/self/io`
+fn proc_io() -> Option<(u64, u64)> {
+	let content = std::fs::read_to_string("/proc/self/io").ok()?;
+	let field = |name: &str| content.lines()
+		.find_map(|line| line.strip_prefix(name))
+		.and_then(|value| value.trim().parse().ok());
+	Some((field("read_bytes:")?, field("write_bytes:")?))
+}
+
+#[cfg(unix)]
+fn rusage() -> ResourceUsage {
+	let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+	unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) };
+	let seconds = |time: libc::timeval| time.tv_sec as f64 + time.tv_usec as f64 / 1e6;
+	ResourceUsage {
+		user_cpu: seconds(usage.ru_utime),
+		sys_cpu: seconds(usage.ru_stime),
+		major_faults: usage.ru_majflt as u64,
+		minor_faults: usage.ru_minflt as u64,
+		voluntary_switches: usage.ru_nvcsw as u64,
+		involuntary_switches: usage.ru_nivcsw as u64,
+		// Kilobytes on Linux, bytes on macOS
+		peak_rss: if cfg!(target_os = "macos") { usage.ru_maxrss as u64 } else { usage.ru_maxrss as u64 * 1024 },
+		..Default::default()
+	}
+}
+
+#[cfg(not(unix))]
+fn rusage() -> ResourceUsage {
+	ResourceUsage::default()
+}
@@ -0,0 +1,429 @@
+use std::path::Path;
+
+use libmdbx::{SyncMode, TableFlags};
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+use crate::{JOB_DIVIDE, MAX_MAP_SIZE, MAX_READERS};
+
+/// Workload used when no scenario file is given on the command line
+const DEFAULT_SCENARIO: &str = include_str!("../scenarios/default.toml");
+
+/// Size of Cuprate's `BlockInfo` & `RctOutput`
+pub const BLOCK_INFO_SIZE: usize = 88;
+pub const RCT_OUTPUT_SIZE: usize = 80;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Scenario {
+	pub name: String,
+	#[serde(default = "default_iterations")]
+	pub iterations: u64,
+	pub benchmarks: Vec<BenchmarkSpec>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BenchmarkSpec {
+	pub name: String,
+	/// Replaced by Cuprate's tables when `chain` is set
+	#[serde(default)]
+	pub tables: Vec<TableSpec>,
+	/// Blocks of the block import workload
+	#[serde(default)]
+	pub chain: Option<ChainSpec>,
+	pub configurations: Vec<ConfigSpec>,
+}
+
+/// Simulated chain of the block import workload, every block writes its header, blob, transactions,
+/// key images & RingCT outputs to Cuprate's tables in the same transaction
+#[derive(Deserialize, Debug, Clone)]
+pub struct ChainSpec {
+	pub blocks: usize,
+	/// Transactions of a block, without the miner transaction
+	pub txs_per_block: SizeSpec,
+	pub inputs_per_tx: SizeSpec,
+	pub outputs_per_tx: SizeSpec,
+	/// Block header & miner transaction, the hashes of the transactions are added
+	pub block_blob_size: SizeSpec,
+	pub tx_blob_size: SizeSpec,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TableSpec {
+	pub name: String,
+	pub key_size: SizeSpec,
+	pub value_size: SizeSpec,
+	pub entries: usize,
+	#[serde(default)]
+	pub flags: Vec<TableFlag>,
+}
+
+/// Length of keys or values, either `32` or `{ min = 32, max = 1048576 }` for a uniform distribution
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum SizeSpec {
+	Fixed(usize),
+	Range { min: usize, max: usize },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigSpec {
+	pub name: String,
+	pub workload: Workload,
+	pub kind: MapKind,
+	pub sync_mode: SyncKind,
+	#[serde(default = "default_job_divide")]
+	pub job_divide: u64,
+	/// Bytes of keys and values per transaction of the put workload instead of `job_divide` entries, at most a whole table
+	#[serde(default)]
+	pub batch_bytes: Option<usize>,
+	/// Reader threads looking up committed entries while the mixed workload puts,
+	/// or the most reader threads of the read scaling workload, 0 for one per core
+	#[serde(default)]
+	pub readers: usize,
+	/// Size of the new values of the update workload, relative to the size of the table's values
+	#[serde(default)]
+	pub update: UpdateSize,
+	/// Fraction of the entries of every table removed by the delete workload
+	#[serde(default = "default_delete_fraction")]
+	pub delete_fraction: f64,
+	/// Which entries the delete workload removes
+	#[serde(default)]
+	pub delete_order: DeleteOrder,
+	/// Bytes the MDBX file grows by when it is full, `GROWTH_STEP` when not set
+	#[serde(default)]
+	pub growth_step: Option<usize>,
+	/// Size the MDBX file is preallocated to and never shrinks below, in bytes
+	#[serde(default)]
+	pub lower_size: usize,
+	/// Free space at the end of the MDBX file above which it is shrunk, in bytes. 0 never shrinks, MDBX uses twice the growth step when not set
+	#[serde(default)]
+	pub shrink_threshold: Option<usize>,
+	/// Page size of the MDBX file, a power of two from 256 B to 64 KiB. MDBX uses the OS page size when not set
+	#[serde(default)]
+	pub page_size: Option<usize>,
+	/// Blocks popped & applied again by every reorganization of the reorg workload
+	#[serde(default = "default_reorg_depth")]
+	pub reorg_depth: usize,
+	/// Oust the reader of the long-lived read workload with MDBX's Handle-Slow-Readers callback instead of growing the file
+	#[serde(default)]
+	pub hsr: bool,
+	/// Only run when `--extended` is given
+	#[serde(default)]
+	pub extended: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Workload {
+	Put,
+	Read,
+	/// Put with `readers` threads reading concurrently
+	Mixed,
+	/// Put, then read with 1, 2, 4... up to `readers` threads
+	#[serde(rename = "read_scaling")]
+	ReadScaling,
+	/// Put, overwrite every value while a read transaction is held open, then overwrite them again once it is closed
+	#[serde(rename = "long_read")]
+	LongRead,
+	/// Put, then overwrite every value with one of `update` size
+	Update,
+	/// Put, delete `delete_fraction` of the entries in `delete_order`, then insert them again
+	Delete,
+	/// Import the blocks of the benchmark's `chain`, `job_divide` blocks per transaction
+	#[serde(rename = "block_import")]
+	BlockImport,
+	/// Import the chain, then pop its top `reorg_depth` blocks and apply other ones in their place, several times
+	Reorg,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteOrder {
+	/// Entries picked at random
+	#[default]
+	Random,
+	/// First inserted entries first, like a shrinking txpool
+	Oldest,
+	/// Stripes of 4096 entries out of every 8, like Monero's pruning
+	Strided,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateSize {
+	#[default]
+	Same,
+	/// Twice as large
+	Larger,
+	/// Half as large
+	Smaller,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapKind {
+	WriteMap,
+	NoWriteMap,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncKind {
+	Durable,
+	NoMetaSync,
+	SafeNoSync,
+	UtterlyNoSync,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TableFlag {
+	ReverseKey,
+	DupSort,
+	IntegerKey,
+	DupFixed,
+	IntegerDup,
+	ReverseDup,
+}
+
+impl Scenario {
+
+	/// Load a scenario from a `.json` or `.toml` file
+	pub fn load(path: &Path) -> Scenario {
+		let content = std::fs::read_to_string(path).expect("Can't read scenario file");
+		let scenario: Scenario = match path.extension().and_then(|ext| ext.to_str()) {
+			Some("json") => serde_json::from_str(&content).expect("Invalid JSON scenario"),
+			_ => toml::from_str(&content).expect("Invalid TOML scenario"),
+		};
+		scenario.with_chain_tables()
+	}
+
+	/// Replace the tables of the block import benchmarks with Cuprate's, then check the scenario
+	fn with_chain_tables(mut self) -> Scenario {
+		for benchmark in &mut self.benchmarks {
+			if let Some(chain) = &benchmark.chain {
+				benchmark.tables = chain.tables();
+			}
+		}
+		self.validate();
+		self
+	}
+
+	/// Multiply the entry count of every table, or the blocks of a chain, by `factor`, keeping at least one.
+	/// Transactions larger than a table hold all its entries
+	pub fn scale(&mut self, factor: f64) {
+		assert!(factor > 0.0 && factor.is_finite(), "scale must be a positive number");
+		if factor == 1.0 {
+			return;
+		}
+		for benchmark in &mut self.benchmarks {
+			if let Some(chain) = &mut benchmark.chain {
+				chain.blocks = ((chain.blocks as f64 * factor).round() as usize).max(1);
+				benchmark.tables = chain.tables();
+				continue;
+			}
+			for table in &mut benchmark.tables {
+				table.entries = ((table.entries as f64 * factor).round() as usize).max(1);
+			}
+		}
+	}
+
+	fn validate(&self) {
+		for benchmark in &self.benchmarks {
+			for config in &benchmark.configurations {
+				assert!(config.job_divide > 0, "job_divide of '{}' must be greater than 0", config.name);
+				assert!(matches!(config.workload, Workload::BlockImport | Workload::Reorg) == benchmark.chain.is_some(), "configuration '{}' must use the block_import or reorg workload if and only if its benchmark has a chain", config.name);
+				assert!(config.reorg_depth > 0, "reorg_depth of '{}' must be greater than 0", config.name);
+				assert!(config.workload != Workload::Mixed || config.readers > 0, "mixed configuration '{}' needs at least one reader", config.name);
+				for table in benchmark.tables.iter().filter(|table| table.flags.contains(&TableFlag::DupFixed)) {
+					assert!(config.workload != Workload::Update || config.update == UpdateSize::Same, "DUP_FIXED table '{}' can't be updated with values of another size in '{}'", table.name, config.name);
+				}
+				assert!((0.0..=1.0).contains(&config.delete_fraction), "delete_fraction of '{}' must be between 0 and 1", config.name);
+				assert!(config.batch_bytes != Some(0), "batch_bytes of '{}' must be greater than 0", config.name);
+				assert!(config.batch_bytes.is_none() || config.workload == Workload::Put, "batch_bytes of '{}' is only supported by the put workload", config.name);
+				assert!(config.growth_step != Some(0), "growth_step of '{}' must be greater than 0", config.name);
+				assert!(config.lower_size < MAX_MAP_SIZE, "lower_size of '{}' must be smaller than the map size", config.name);
+				if let Some(page_size) = config.page_size {
+					assert!(page_size.is_power_of_two() && (256..=65536).contains(&page_size), "page_size of '{}' must be a power of two from 256 to 65536", config.name);
+				}
+				assert!(config.readers < MAX_READERS, "configuration '{}' has more than {} readers", config.name, MAX_READERS - 1);
+			}
+			if let Some(chain) = &benchmark.chain {
+				assert!(chain.blocks > 0, "chain of benchmark '{}' needs at least one block", benchmark.name);
+				for config in benchmark.configurations.iter().filter(|config| config.workload == Workload::Reorg) {
+					assert!(config.reorg_depth <= chain.blocks, "reorg_depth of '{}' is deeper than the chain", config.name);
+				}
+				for size in [chain.txs_per_block, chain.inputs_per_tx, chain.outputs_per_tx, chain.block_blob_size, chain.tx_blob_size] {
+					if let SizeSpec::Range { min, max } = size {
+						assert!(min <= max, "size range of the chain of benchmark '{}' has min greater than max", benchmark.name);
+					}
+				}
+			}
+			for table in &benchmark.tables {
+				assert!(table.entries > 0, "entries of table '{}' must be greater than 0", table.name);
+				for size in [table.key_size, table.value_size] {
+					if let SizeSpec::Range { min, max } = size {
+						assert!(min <= max, "size range of table '{}' has min greater than max", table.name);
+					}
+				}
+				if table.flags.contains(&TableFlag::DupFixed) {
+					assert!(matches!((table.key_size, table.value_size), (SizeSpec::Fixed(_), SizeSpec::Fixed(_))), "DUP_FIXED table '{}' needs fixed key and value sizes", table.name);
+				}
+			}
+		}
+	}
+}
+
+impl Default for Scenario {
+	fn default() -> Self {
+		toml::from_str(DEFAULT_SCENARIO).expect("Invalid default scenario")
+	}
+}
+
+impl ConfigSpec {
+
+	/// Most reader threads of the read scaling workload, leaving a reader slot for opening tables
+	pub fn max_readers(&self) -> usize {
+		match self.readers {
+			0 => std::thread::available_parallelism().map_or(1, |cores| cores.get()).min(MAX_READERS - 1),
+			readers => readers,
+		}
+	}
+
+	/// Whether the configuration sets the geometry of the file, which only MDBX has
+	pub fn sets_geometry(&self) -> bool {
+		self.growth_step.is_some() || self.lower_size > 0 || self.shrink_threshold.is_some() || self.page_size.is_some()
+	}
+
+	/// Entries per transaction of the put workload on `table`, `batch_bytes` is converted with the mean entry size
+	pub fn batch_entries(&self, table: &TableSpec) -> u64 {
+		match self.batch_bytes {
+			Some(bytes) => (bytes / (table.key_size.mean() + table.value_size.mean()).max(1)).clamp(1, table.entries) as u64,
+			None => self.job_divide,
+		}
+	}
+}
+
+impl ChainSpec {
+
+	/// Cuprate's tables written by the blocks, with their expected entry counts
+	pub fn tables(&self) -> Vec<TableSpec> {
+		let blocks = self.blocks as f64;
+		let txs = blocks * self.txs_per_block.mean() as f64;
+		let table = |name: &str, key_size: usize, value_size: SizeSpec, entries: f64| TableSpec {
+			name: name.to_string(),
+			key_size: SizeSpec::Fixed(key_size),
+			value_size,
+			entries: (entries.round() as usize).max(1),
+			flags: if key_size == 8 { vec![TableFlag::IntegerKey] } else { Vec::new() },
+		};
+		let range = |size: SizeSpec, per_item: usize, items: SizeSpec| SizeSpec::Range { min: size.min() + per_item * items.min(), max: size.max() + per_item * items.max() };
+		vec![
+			table("block_infos", 8, SizeSpec::Fixed(BLOCK_INFO_SIZE), blocks),
+			table("block_blobs", 8, range(self.block_blob_size, 32, self.txs_per_block), blocks),
+			table("block_heights", 32, SizeSpec::Fixed(8), blocks),
+			table("key_images", 32, SizeSpec::Fixed(0), txs * self.inputs_per_tx.mean() as f64),
+			table("rct_outputs", 8, SizeSpec::Fixed(RCT_OUTPUT_SIZE), blocks + txs * self.outputs_per_tx.mean() as f64),
+			table("tx_blobs", 8, self.tx_blob_size, txs),
+			table("tx_ids", 32, SizeSpec::Fixed(8), txs),
+			table("tx_heights", 8, SizeSpec::Fixed(8), txs),
+			table("tx_outputs", 8, range(SizeSpec::Fixed(0), 8, self.outputs_per_tx), txs),
+		]
+	}
+}
+
+impl TableSpec {
+
+	/// DupSort tables store key and value concatenated as duplicates of a zero-length key
+	pub fn is_dup(&self) -> bool {
+		self.flags.contains(&TableFlag::DupSort)
+	}
+
+	pub fn table_flags(&self) -> TableFlags {
+		self.flags.iter().fold(TableFlags::empty(), |flags, flag| flags.union(flag.into()))
+	}
+}
+
+impl SizeSpec {
+
+	pub fn sample<G: Rng + ?Sized>(&self, rng: &mut G) -> usize {
+		match *self {
+			SizeSpec::Fixed(size) => size,
+			SizeSpec::Range { min, max } => rng.gen_range(min..=max),
+		}
+	}
+
+	pub fn max(&self) -> usize {
+		match *self {
+			SizeSpec::Fixed(size) => size,
+			SizeSpec::Range { max, .. } => max,
+		}
+	}
+
+	pub fn min(&self) -> usize {
+		match *self {
+			SizeSpec::Fixed(size) => size,
+			SizeSpec::Range { min, .. } => min,
+		}
+	}
+
+	pub fn mean(&self) -> usize {
+		match *self {
+			SizeSpec::Fixed(size) => size,
+			SizeSpec::Range { min, max } => (min + max) / 2,
+		}
+	}
+}
+
+impl UpdateSize {
+
+	pub fn apply(self, size: SizeSpec) -> SizeSpec {
+		let resize = |size: usize| match self {
+			UpdateSize::Same => size,
+			UpdateSize::Larger => size * 2,
+			UpdateSize::Smaller => (size / 2).max(1),
+		};
+		match size {
+			SizeSpec::Fixed(size) => SizeSpec::Fixed(resize(size)),
+			SizeSpec::Range { min, max } => SizeSpec::Range { min: resize(min), max: resize(max) },
+		}
+	}
+}
+
+impl From<&TableFlag> for TableFlags {
+	fn from(flag: &TableFlag) -> Self {
+		match flag {
+			TableFlag::ReverseKey => TableFlags::REVERSE_KEY,
+			TableFlag::DupSort => TableFlags::DUP_SORT,
+			TableFlag::IntegerKey => TableFlags::INTEGER_KEY,
+			TableFlag::DupFixed => TableFlags::DUP_FIXED,
+			TableFlag::IntegerDup => TableFlags::INTEGER_DUP,
+			TableFlag::ReverseDup => TableFlags::REVERSE_DUP,
+		}
+	}
+}
+
+impl From<SyncKind> for SyncMode {
+	fn from(sync: SyncKind) -> Self {
+		match sync {
+			SyncKind::Durable => SyncMode::Durable,
+			SyncKind::NoMetaSync => SyncMode::NoMetaSync,
+			SyncKind::SafeNoSync => SyncMode::SafeNoSync,
+			SyncKind::UtterlyNoSync => SyncMode::UtterlyNoSync,
+		}
+	}
+}
+
+fn default_iterations() -> u64 {
+	3
+}
+
+fn default_delete_fraction() -> f64 {
+	0.5
+}
+
+fn default_reorg_depth() -> usize {
+	1
+}
+
+fn default_job_divide() -> u64 {
+	JOB_DIVIDE
+}
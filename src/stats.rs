@@ -0,0 +1,257 @@
+use serde::{Serialize, Deserialize};
+
+use crate::report::Phase;
+
+/// Aggregates of a set of samples
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct Summary {
+	pub mean: f64,
+	pub median: f64,
+	pub min: f64,
+	pub max: f64,
+	/// Sample standard deviation, 0 with a single sample
+	pub stddev: f64,
+	/// Coefficient of variation, `stddev / mean`
+	pub cv: f64,
+}
+
+impl Summary {
+
+	pub fn of(samples: &[f64]) -> Summary {
+		if samples.is_empty() {
+			return Summary::default();
+		}
+
+		let mut sorted = samples.to_vec();
+		sorted.sort_by(f64::total_cmp);
+
+		let len = sorted.len();
+		let mean = sorted.iter().sum::<f64>() / len as f64;
+		let median = match len % 2 {
+			0 => (sorted[len/2 - 1] + sorted[len/2]) / 2.0,
+			_ => sorted[len/2],
+		};
+		let stddev = match len {
+			1 => 0.0,
+			_ => (sorted.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / (len - 1) as f64).sqrt(),
+		};
+		let cv = if mean > 0.0 { stddev / mean } else { 0.0 };
+
+		Summary { mean, median, min: sorted[0], max: sorted[len - 1], stddev, cv }
+	}
+}
+
+/// Sub-buckets per power of two, values are recorded with less than 1% error
+const SUB_BUCKET_BITS: u32 = 7;
+const SUB_BUCKET_MASK: usize = (1 << SUB_BUCKET_BITS) - 1;
+const BUCKETS: usize = (64 - SUB_BUCKET_BITS as usize + 1) << SUB_BUCKET_BITS;
+
+/// HDR-style log-linear histogram of durations in nanoseconds
+#[derive(Debug, Clone)]
+pub struct Histogram {
+	counts: Vec<u64>,
+	total: u64,
+	max: u64,
+}
+
+impl Default for Histogram {
+	fn default() -> Self {
+		Histogram { counts: vec![0; BUCKETS], total: 0, max: 0 }
+	}
+}
+
+impl Histogram {
+
+	pub fn record(&mut self, nanos: u64) {
+		self.counts[bucket_index(nanos)] += 1;
+		self.total += 1;
+		self.max = self.max.max(nanos);
+	}
+
+	pub fn merge(&mut self, other: &Histogram) {
+		self.counts.iter_mut().zip(&other.counts).for_each(|(count, other)| *count += other);
+		self.total += other.total;
+		self.max = self.max.max(other.max);
+	}
+
+	/// Highest value of the bucket holding the `quantile` (0.0 to 1.0) of the samples
+	pub fn value_at(&self, quantile: f64) -> u64 {
+		let rank = ((quantile * self.total as f64).ceil() as u64).max(1);
+		let mut seen = 0;
+		for (index, count) in self.counts.iter().enumerate() {
+			seen += count;
+			if seen >= rank {
+				return (bucket_lowest(index + 1) - 1).min(self.max);
+			}
+		}
+		self.max
+	}
+
+	pub fn summary(&self) -> LatencySummary {
+		let seconds = |nanos: u64| nanos as f64 / 1e9;
+		LatencySummary {
+			count: self.total,
+			p50: seconds(self.value_at(0.5)),
+			p90: seconds(self.value_at(0.9)),
+			p99: seconds(self.value_at(0.99)),
+			p999: seconds(self.value_at(0.999)),
+			max: seconds(self.max),
+		}
+	}
+}
+
+fn bucket_index(value: u64) -> usize {
+	if value <= SUB_BUCKET_MASK as u64 {
+		return value as usize;
+	}
+	let shift = 63 - value.leading_zeros() - SUB_BUCKET_BITS;
+	((shift as usize + 1) << SUB_BUCKET_BITS) + ((value >> shift) as usize & SUB_BUCKET_MASK)
+}
+
+fn bucket_lowest(index: usize) -> u64 {
+	let (block, sub) = (index >> SUB_BUCKET_BITS, (index & SUB_BUCKET_MASK) as u64);
+	match block {
+		0 => sub,
+		_ => ((1 << SUB_BUCKET_BITS) + sub) << (block - 1),
+	}
+}
+
+/// Percentiles of a latency histogram, in seconds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct LatencySummary {
+	pub count: u64,
+	pub p50: f64,
+	pub p90: f64,
+	pub p99: f64,
+	pub p999: f64,
+	pub max: f64,
+}
+
+/// Time spent in each stage of commits, in seconds, summed over `commits`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct CommitBreakdown {
+	pub commits: u64,
+	pub preparation: f64,
+	pub gc_wallclock: f64,
+	pub gc_cputime: f64,
+	pub audit: f64,
+	pub write: f64,
+	pub sync: f64,
+	pub ending: f64,
+	pub whole: f64,
+}
+
+impl CommitBreakdown {
+
+	pub fn add(&mut self, other: &CommitBreakdown) {
+		self.commits += other.commits;
+		self.preparation += other.preparation;
+		self.gc_wallclock += other.gc_wallclock;
+		self.gc_cputime += other.gc_cputime;
+		self.audit += other.audit;
+		self.write += other.write;
+		self.sync += other.sync;
+		self.ending += other.ending;
+		self.whole += other.whole;
+	}
+}
+
+/// Latencies of the write transactions and of the readers of the mixed workload
+#[derive(Debug, Clone, Default)]
+pub struct Latencies {
+	/// Transactions of every phase writing, in the order the phases first ran
+	pub writes: Vec<WriteLatencies>,
+	/// Duration of every lookup of the reader threads, read transaction included
+	pub read: Histogram,
+	/// Stages of the commits of every phase, for engines reporting them
+	pub breakdown: CommitBreakdown,
+}
+
+#[derive(Debug, Clone)]
+pub struct WriteLatencies {
+	pub phase: Phase,
+	/// Duration of every commit
+	pub commit: Histogram,
+	/// Duration of the writes of every transaction, without the commit
+	pub batch: Histogram,
+}
+
+impl Latencies {
+
+	/// Record a transaction of `phase`, its writes took `batch_nanos` and its commit `commit_nanos`
+	pub fn record_write(&mut self, phase: Phase, batch_nanos: u64, commit_nanos: u64, breakdown: Option<CommitBreakdown>) {
+		let writes = self.writes(phase);
+		writes.batch.record(batch_nanos);
+		writes.commit.record(commit_nanos);
+		if let Some(breakdown) = breakdown {
+			self.breakdown.add(&breakdown);
+		}
+	}
+
+	pub fn merge(&mut self, other: &Latencies) {
+		for other in &other.writes {
+			let writes = self.writes(other.phase);
+			writes.commit.merge(&other.commit);
+			writes.batch.merge(&other.batch);
+		}
+		self.read.merge(&other.read);
+		self.breakdown.add(&other.breakdown);
+	}
+
+	fn writes(&mut self, phase: Phase) -> &mut WriteLatencies {
+		match self.writes.iter().position(|writes| writes.phase == phase) {
+			Some(index) => &mut self.writes[index],
+			None => {
+				self.writes.push(WriteLatencies { phase, commit: Histogram::default(), batch: Histogram::default() });
+				self.writes.last_mut().unwrap()
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bucket_round_trip() {
+		let mut value = 1;
+		while value < 1 << 40 {
+			for value in [value, value + 1, value * 3 / 2] {
+				let index = bucket_index(value);
+				let (lowest, highest) = (bucket_lowest(index), bucket_lowest(index + 1) - 1);
+				assert!(lowest <= value && value <= highest, "{value} outside of bucket {index} [{lowest}, {highest}]");
+				assert!((highest - lowest) as f64 <= value as f64 / 100.0, "bucket {index} of {value} is [{lowest}, {highest}]");
+			}
+			value *= 2;
+		}
+	}
+
+	#[test]
+	fn percentiles() {
+		let mut histogram = Histogram::default();
+		(1..=1000).for_each(|micros| histogram.record(micros * 1000));
+		let close = |value: u64, expected: u64| assert!(value.abs_diff(expected) as f64 <= expected as f64 / 100.0, "{value} instead of {expected}");
+		close(histogram.value_at(0.5), 500_000);
+		close(histogram.value_at(0.99), 990_000);
+		assert_eq!(histogram.value_at(1.0), 1_000_000);
+
+		let summary = histogram.summary();
+		assert_eq!(summary.count, 1000);
+		assert_eq!(summary.max, 1e-3);
+
+		// Merging doubles the counts without moving the percentiles
+		let mut merged = histogram.clone();
+		merged.merge(&histogram);
+		assert_eq!(merged.summary().count, 2000);
+		assert_eq!(merged.value_at(0.5), histogram.value_at(0.5));
+	}
+
+	#[test]
+	fn empty_histogram() {
+		let histogram = Histogram::default();
+		assert_eq!(histogram.value_at(0.5), 0);
+		let summary = histogram.summary();
+		assert_eq!((summary.count, summary.p50, summary.p999, summary.max), (0, 0.0, 0.0, 0.0));
+	}
+}
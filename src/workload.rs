@@ -0,0 +1,601 @@
+use std::{time::{Instant, Duration}, sync::{Barrier, Mutex, mpsc, atomic::{AtomicBool, AtomicUsize, Ordering}}, thread, ops::Range};
+
+use indicatif::ProgressBar;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, RngCore, SeedableRng};
+
+use crate::{backend::StorageBackend, chain::{Chain, ChainTip, Block}, scenario::{ConfigSpec, TableSpec, SizeSpec, DeleteOrder}, report::{TableMeasurement, Phase, GrowthSample, ReorgReport}, stats::{Latencies, Histogram}, resources::{Sample, ResourceUsage}};
+
+/// Deterministic generator of the key/value pairs of a table, entry `index` is the same every time it is generated.
+/// Entries are generated one batch at a time, outside of the measured time & resources, and regenerated for the read phase.
+pub struct Entries<'a> {
+	pub spec: &'a TableSpec,
+	seed: u64,
+	/// Number of times the values were overwritten, keys stay the same
+	pub generation: u64,
+	/// Size of the overwritten values
+	value_size: SizeSpec,
+	/// Entries committed so far, for the readers of the mixed workload
+	pub committed: AtomicUsize,
+}
+
+impl<'a> Entries<'a> {
+
+	pub fn new(spec: &'a TableSpec, seed: u64) -> Entries<'a> {
+		Entries { spec, seed, generation: 0, value_size: spec.value_size, committed: AtomicUsize::new(0) }
+	}
+
+	/// Same keys with the values of `generation`
+	pub fn overwrite(&self, generation: u64) -> Entries<'a> {
+		Entries { spec: self.spec, seed: self.seed, generation, value_size: self.value_size, committed: AtomicUsize::new(0) }
+	}
+
+	/// Overwritten values of `value_size` instead of the size of the table
+	pub fn resized(self, value_size: SizeSpec) -> Entries<'a> {
+		Entries { value_size, ..self }
+	}
+
+	/// Key/value pair `index`. DupSort tables get their key & value concatenated under a zero-length key
+	pub fn entry(&self, index: usize) -> (Vec<u8>, Vec<u8>) {
+		let mut rng = StdRng::seed_from_u64(self.seed ^ index as u64);
+		let key_size = self.spec.key_size.sample(&mut rng);
+		let mut value_size = self.spec.value_size.sample(&mut rng);
+
+		// The key is a prefix of the random stream of the first generation, overwritten values get their own stream
+		if self.generation > 0 {
+			let mut key = vec![0u8; key_size];
+			rng.fill_bytes(&mut key);
+			rng = StdRng::seed_from_u64(self.seed ^ index as u64 ^ self.generation.rotate_right(16));
+			value_size = self.value_size.sample(&mut rng);
+			let mut value = vec![0u8; value_size];
+			rng.fill_bytes(&mut value);
+			return match self.spec.is_dup() {
+				true => (Vec::new(), [key, value].concat()),
+				false => (key, value),
+			};
+		}
+
+		let mut buf = vec![0u8; key_size+value_size];
+		rng.fill_bytes(&mut buf);
+		if self.spec.is_dup() {
+			(Vec::new(), buf)
+		} else {
+			let value = buf.split_off(key_size);
+			(buf, value)
+		}
+	}
+
+	/// Number of transactions of `job_divide` entries, the last one holds the remaining entries
+	pub fn batches(&self, job_divide: u64) -> usize {
+		self.spec.entries.div_ceil(job_divide as usize)
+	}
+
+	/// Entries of the transaction `batch`, in insertion order
+	pub fn batch(&self, batch: usize, job_divide: u64) -> Vec<(Vec<u8>, Vec<u8>)> {
+		let first = batch * job_divide as usize;
+		(first..(first + job_divide as usize).min(self.spec.entries)).map(|index| self.entry(index)).collect()
+	}
+}
+
+/// Put every entry of the table, overwritten generations of DupSort tables delete the previous values first
+pub fn benchmark_put_table<B: StorageBackend>(
+	pg: &ProgressBar,
+	db: &B,
+	entries: &Entries,
+	phase: Phase,
+	job_divide: u64,
+	measurements: &mut Vec<TableMeasurement>,
+	latencies: &mut Latencies)
+{
+	let spec = entries.spec;
+
+	// Open table
+	let table = db.open_table(spec);
+
+	let is_dup = spec.is_dup();
+
+	// Measurement
+	let mut resources = ResourceUsage::zero();
+	let mut elapsed = Duration::ZERO;
+	let mut bytes = 0;
+
+	(0..entries.batches(job_divide)).for_each(|batch| {
+
+		// Generate random data
+		let data = entries.batch(batch, job_divide);
+		let previous = (is_dup && entries.generation > 0).then(|| entries.overwrite(entries.generation - 1).batch(batch, job_divide));
+		bytes += data_size(&data);
+
+		// Zero-key values are inserted with a cursor
+		let sample = Sample::now();
+		let instant = Instant::now();
+		let mut rw_tx = db.begin_rw();
+		if let Some(previous) = &previous {
+			previous.iter().for_each(|previous| { B::delete(&mut rw_tx, &table, B::ZERO_KEY, Some(&previous.1)); });
+		}
+		if is_dup {
+			let mut cursor = B::rw_cursor(&mut rw_tx, &table);
+			data.iter().for_each(|data| B::cursor_put(&mut cursor, B::ZERO_KEY, &data.1));
+		} else {
+			data.iter().for_each(|data| B::put(&mut rw_tx, &table, &data.0, &data.1));
+		}
+
+		timed_commit::<B>(rw_tx, phase, instant, latencies);
+		elapsed += instant.elapsed();
+		resources.add(&sample.elapsed());
+		entries.committed.fetch_add(data.len(), Ordering::Release);
+		pg.inc(data.len() as u64);
+	});
+
+	let ops = spec.entries as u64;
+	measurements.push(TableMeasurement { table: spec.name.clone(), phase, seconds: elapsed.as_secs_f64(), ops, bytes, resources: Some(resources), threads: None });
+}
+
+pub fn benchmark_read_table<B: StorageBackend>(
+	pg: &ProgressBar,
+	db: &B,
+	entries: &Entries,
+	rng: &mut StdRng,
+	job_divide: u64,
+	measurements: &mut Vec<TableMeasurement>)
+{
+	let spec = entries.spec;
+
+	// Open table
+	let table = db.open_table(spec);
+
+	// Only the order of the entries is kept in memory
+	let mut order: Vec<usize> = (0..spec.entries).collect();
+	order.shuffle(rng);
+
+	let is_dup = spec.is_dup();
+
+	// Measurement
+	let mut resources = ResourceUsage::zero();
+	let mut elapsed = Duration::ZERO;
+	let mut bytes = 0;
+
+	order.chunks(job_divide as usize).for_each(|batch| {
+
+		// Regenerate the data of the batch
+		let data: Vec<(Vec<u8>, Vec<u8>)> = batch.iter().map(|&index| entries.entry(index)).collect();
+		bytes += data_size(&data);
+
+		// Zero-key values are looked up with a cursor
+		let sample = Sample::now();
+		let instant = Instant::now();
+		let ro_tx = db.begin_ro();
+		let mut cursor = is_dup.then(|| B::ro_cursor(&ro_tx, &table));
+
+		data.iter().for_each(|data| {
+			let found = match cursor.as_mut() {
+				Some(cursor) => B::seek(cursor, B::ZERO_KEY, &data.1),
+				None => B::get(&ro_tx, &table, &data.0),
+			};
+			assert!(found, "Entry not found in {}", spec.name);
+		});
+		drop(cursor);
+		drop(ro_tx);
+		elapsed += instant.elapsed();
+		resources.add(&sample.elapsed());
+		pg.inc(batch.len() as u64);
+	});
+
+	measurements.push(TableMeasurement { table: spec.name.clone(), phase: Phase::Read, seconds: elapsed.as_secs_f64(), ops: order.len() as u64, bytes, resources: Some(resources), threads: None });
+}
+
+/// 1, 2, 4... threads, up to `max` which is always included
+pub fn thread_counts(max: usize) -> Vec<usize> {
+	let mut counts: Vec<usize> = (0..).map(|shift| 1 << shift).take_while(|&threads| threads < max).collect();
+	counts.push(max);
+	counts
+}
+
+/// Look up every entry in random order with each of `thread_counts`, the batches of `job_divide` lookups being spread over the threads,
+/// each batch in its own read transaction. Each thread generates what it looks up before the threads start together, so they only measure the engine
+pub fn benchmark_read_scaling<B: StorageBackend>(
+	pg: &ProgressBar,
+	db: &B,
+	entries: &Entries,
+	rng: &mut StdRng,
+	job_divide: u64,
+	thread_counts: &[usize],
+	measurements: &mut Vec<TableMeasurement>)
+{
+	let spec = entries.spec;
+	let is_dup = spec.is_dup();
+
+	// Only the order of the entries is kept for every thread count
+	let mut order: Vec<usize> = (0..spec.entries).collect();
+	order.shuffle(rng);
+
+	for &threads in thread_counts {
+		let barrier = Barrier::new(threads);
+		let batches: Vec<&[usize]> = order.chunks(job_divide as usize).collect();
+
+		// The phase lasts from the first thread starting to the last one finishing
+		let spans: Vec<(Instant, Instant, u64)> = thread::scope(|scope| {
+			let handles: Vec<_> = (0..threads).map(|thread| {
+				let (barrier, batches) = (&barrier, &batches);
+				scope.spawn(move || {
+					let table = db.open_table(spec);
+
+					// Keys, or values of DupSort tables, of the batches of this thread flattened in one buffer
+					let mut lookups = Vec::new();
+					let mut bytes = 0;
+					let ranges: Vec<Vec<Range<usize>>> = batches.iter().skip(thread).step_by(threads).map(|batch| {
+						batch.iter().map(|&index| {
+							let data = entries.entry(index);
+							bytes += data_size(std::slice::from_ref(&data));
+							let start = lookups.len();
+							lookups.extend_from_slice(if is_dup { &data.1 } else { &data.0 });
+							start..lookups.len()
+						}).collect()
+					}).collect();
+
+					barrier.wait();
+					let start = Instant::now();
+					ranges.iter().for_each(|batch| {
+						let ro_tx = db.begin_ro();
+						let mut cursor = is_dup.then(|| B::ro_cursor(&ro_tx, &table));
+						batch.iter().for_each(|range| {
+							let found = match cursor.as_mut() {
+								Some(cursor) => B::seek(cursor, B::ZERO_KEY, &lookups[range.clone()]),
+								None => B::get(&ro_tx, &table, &lookups[range.clone()]),
+							};
+							assert!(found, "Entry not found in {}", spec.name);
+						});
+						drop(cursor);
+						drop(ro_tx);
+						pg.inc(batch.len() as u64);
+					});
+					(start, Instant::now(), bytes)
+				})
+			}).collect();
+			handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+		});
+		let elapsed = spans.iter().map(|span| span.1).max().unwrap() - spans.iter().map(|span| span.0).min().unwrap();
+		let bytes = spans.iter().map(|span| span.2).sum();
+
+		measurements.push(TableMeasurement { table: spec.name.clone(), phase: Phase::Read, seconds: elapsed.as_secs_f64(), ops: order.len() as u64, bytes, resources: None, threads: Some(threads) });
+	}
+}
+
+/// Put every table while `config.readers` threads look up random entries already committed, each in its own read transaction
+pub fn benchmark_mixed_tables<B: StorageBackend>(
+	pg: &ProgressBar,
+	db: &B,
+	entries: &[Entries],
+	config: &ConfigSpec,
+	rng: &mut StdRng,
+	measurements: &mut Vec<TableMeasurement>,
+	latencies: &mut Latencies)
+{
+	let done = AtomicBool::new(false);
+	let seeds: Vec<u64> = (0..config.readers).map(|_| rng.next_u64()).collect();
+
+	let (elapsed, reads) = thread::scope(|scope| {
+		let handles: Vec<_> = seeds.into_iter().map(|seed| {
+			let done = &done;
+			scope.spawn(move || read_committed(db, entries, done, seed))
+		}).collect();
+
+		let instant = Instant::now();
+		entries.iter().for_each(|entries| benchmark_put_table(pg, db, entries, Phase::Put, config.job_divide, measurements, latencies));
+		let elapsed = instant.elapsed();
+		done.store(true, Ordering::Relaxed);
+
+		let reads: Vec<(Histogram, Vec<(u64, u64)>)> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+		(elapsed, reads)
+	});
+
+	// Readers are measured over the whole put phase of every table
+	for (table, entries) in entries.iter().enumerate() {
+		let (ops, bytes) = reads.iter().fold((0, 0), |(ops, bytes), (_, tables)| (ops + tables[table].0, bytes + tables[table].1));
+		measurements.push(TableMeasurement { table: entries.spec.name.clone(), phase: Phase::ConcurrentRead, seconds: elapsed.as_secs_f64(), ops, bytes, resources: None, threads: None });
+	}
+	reads.iter().for_each(|(histogram, _)| latencies.read.merge(histogram));
+}
+
+/// Reader thread of the mixed workload, returns the latency of every lookup and the ops & bytes read from each table
+fn read_committed<B: StorageBackend>(db: &B, entries: &[Entries], done: &AtomicBool, seed: u64) -> (Histogram, Vec<(u64, u64)>) {
+	let mut rng = StdRng::seed_from_u64(seed);
+	let tables: Vec<B::Table<'_>> = entries.iter().map(|entries| db.open_table(entries.spec)).collect();
+	let mut histogram = Histogram::default();
+	let mut reads = vec![(0, 0); entries.len()];
+
+	while !done.load(Ordering::Relaxed) {
+		let table = rng.gen_range(0..entries.len());
+		let count = entries[table].committed.load(Ordering::Acquire);
+		if count == 0 {
+			thread::yield_now();
+			continue;
+		}
+		let data = entries[table].entry(rng.gen_range(0..count));
+
+		let instant = Instant::now();
+		let ro_tx = db.begin_ro();
+		let found = match entries[table].spec.is_dup() {
+			true => B::seek(&mut B::ro_cursor(&ro_tx, &tables[table]), B::ZERO_KEY, &data.1),
+			false => B::get(&ro_tx, &tables[table], &data.0),
+		};
+		drop(ro_tx);
+		histogram.record(instant.elapsed().as_nanos() as u64);
+
+		assert!(found, "Committed entry not found by a reader");
+		reads[table].0 += 1;
+		reads[table].1 += data_size(&[data]);
+	}
+	(histogram, reads)
+}
+
+/// Interval between two samples of the file & GC size during the long-lived read workload
+const GROWTH_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Open a read transaction on the filled tables and overwrite every value while it is held, then close it and overwrite them again.
+/// Returns the file & GC size sampled during both overwrites
+pub fn benchmark_long_read<B: StorageBackend>(
+	pg: &ProgressBar,
+	db: &B,
+	entries: &[Entries],
+	job_divide: u64,
+	measurements: &mut Vec<TableMeasurement>,
+	latencies: &mut Latencies) -> Vec<GrowthSample>
+{
+	let (held, done) = (AtomicBool::new(true), AtomicBool::new(false));
+	let samples = Mutex::new(Vec::new());
+	let start = Instant::now();
+	let sample = || {
+		let stats = db.stats(&[]);
+		samples.lock().unwrap().push(GrowthSample { seconds: start.elapsed().as_secs_f64(), held: held.load(Ordering::Relaxed), file_size: db.file_size(), gc: stats.gc });
+	};
+
+	thread::scope(|scope| {
+		// The transaction is begun & ended by its own thread, like an RPC handler would
+		let (opened_tx, opened_rx) = mpsc::channel();
+		let (release_tx, release_rx) = mpsc::channel();
+		let oust_tx = release_tx.clone();
+		let reader = scope.spawn(move || {
+			let ro_tx = db.begin_ro();
+			let (ended_tx, ended_rx) = mpsc::channel();
+			db.allow_oust(&ro_tx, oust_tx, ended_rx);
+			opened_tx.send(()).unwrap();
+			// Released after the overwrites, or during them by the Handle-Slow-Readers callback
+			release_rx.recv().unwrap();
+			drop(ro_tx);
+			let _ = ended_tx.send(());
+		});
+		opened_rx.recv().unwrap();
+
+		scope.spawn(|| {
+			while !done.load(Ordering::Relaxed) {
+				sample();
+				thread::sleep(GROWTH_SAMPLE_INTERVAL);
+			}
+		});
+
+		sample();
+		entries.iter().for_each(|entries| benchmark_put_table(pg, db, &entries.overwrite(1), Phase::OverwriteHeld, job_divide, measurements, latencies));
+		sample();
+
+		// Fails if the callback already ended the transaction
+		let _ = release_tx.send(());
+		reader.join().unwrap();
+		held.store(false, Ordering::Relaxed);
+
+		sample();
+		entries.iter().for_each(|entries| benchmark_put_table(pg, db, &entries.overwrite(2), Phase::Overwrite, job_divide, measurements, latencies));
+		sample();
+		done.store(true, Ordering::Relaxed);
+	});
+
+	let mut samples = samples.into_inner().unwrap();
+	samples.sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+	samples
+}
+
+/// Entries of a pruning stripe, and stripes in a pruning cycle, as in Monero's pruning
+const PRUNING_STRIPE_SIZE: usize = 4096;
+const PRUNING_STRIPES: usize = 8;
+
+/// Entries removed by the delete workload, `job_divide` per transaction in deletion order
+pub fn delete_batches(entries: &Entries, config: &ConfigSpec, rng: &mut StdRng) -> Vec<Vec<usize>> {
+	let inserted = entries.spec.entries;
+	let indices: Vec<usize> = match config.delete_order {
+		DeleteOrder::Random => {
+			let mut order: Vec<usize> = (0..inserted).collect();
+			order.shuffle(rng);
+			order.truncate((inserted as f64 * config.delete_fraction).round() as usize);
+			order
+		},
+		DeleteOrder::Oldest => (0..(inserted as f64 * config.delete_fraction).round() as usize).collect(),
+		DeleteOrder::Strided => {
+			let pruned = (PRUNING_STRIPES as f64 * config.delete_fraction).round() as usize;
+			(0..inserted).filter(|index| (index / PRUNING_STRIPE_SIZE) % PRUNING_STRIPES < pruned).collect()
+		},
+	};
+	indices.chunks(config.job_divide as usize).map(<[usize]>::to_vec).collect()
+}
+
+/// Delete the entries of `batches`, or insert them again for `Phase::Reinsert`, one transaction per batch
+pub fn benchmark_prune_table<B: StorageBackend>(
+	pg: &ProgressBar,
+	db: &B,
+	entries: &Entries,
+	batches: &[Vec<usize>],
+	phase: Phase,
+	measurements: &mut Vec<TableMeasurement>,
+	latencies: &mut Latencies)
+{
+	let spec = entries.spec;
+
+	// Open table
+	let table = db.open_table(spec);
+
+	let is_dup = spec.is_dup();
+
+	// Measurement
+	let mut resources = ResourceUsage::zero();
+	let mut elapsed = Duration::ZERO;
+	let mut bytes = 0;
+
+	batches.iter().for_each(|batch| {
+
+		let data: Vec<_> = batch.iter().map(|&index| entries.entry(index)).collect();
+		bytes += data_size(&data);
+
+		let sample = Sample::now();
+		let instant = Instant::now();
+		let mut rw_tx = db.begin_rw();
+		match (phase, is_dup) {
+			(Phase::Delete, true) => data.iter().for_each(|data| assert!(B::delete(&mut rw_tx, &table, B::ZERO_KEY, Some(&data.1)), "Deleted entry not found")),
+			(Phase::Delete, false) => data.iter().for_each(|data| assert!(B::delete(&mut rw_tx, &table, &data.0, None), "Deleted entry not found")),
+			(_, true) => {
+				let mut cursor = B::rw_cursor(&mut rw_tx, &table);
+				data.iter().for_each(|data| B::cursor_put(&mut cursor, B::ZERO_KEY, &data.1));
+			},
+			(_, false) => data.iter().for_each(|data| B::put(&mut rw_tx, &table, &data.0, &data.1)),
+		}
+
+		timed_commit::<B>(rw_tx, phase, instant, latencies);
+		elapsed += instant.elapsed();
+		resources.add(&sample.elapsed());
+		pg.inc(batch.len() as u64);
+	});
+
+	let ops = batches.iter().map(Vec::len).sum::<usize>() as u64;
+	measurements.push(TableMeasurement { table: spec.name.clone(), phase, seconds: elapsed.as_secs_f64(), ops, bytes, resources: Some(resources), threads: None });
+}
+
+/// Import the blocks of `chain` on top of each other, every transaction writes `job_divide` whole blocks to all the tables.
+/// Returns the tip under every imported block, then the tip of the chain
+pub fn benchmark_block_import<B: StorageBackend>(
+	pg: &ProgressBar,
+	db: &B,
+	chain: &Chain,
+	tables: &[TableSpec],
+	job_divide: u64,
+	measurements: &mut Vec<TableMeasurement>,
+	latencies: &mut Latencies) -> Vec<ChainTip>
+{
+	// Open tables
+	let handles: Vec<B::Table<'_>> = tables.iter().map(|spec| db.open_table(spec)).collect();
+
+	// Measurement
+	let mut resources = ResourceUsage::zero();
+	let mut elapsed = Duration::ZERO;
+	let mut bytes = 0;
+	let mut tips = vec![ChainTip::default()];
+
+	let batches = chain.spec.blocks.div_ceil(job_divide as usize);
+	(0..batches).for_each(|batch| {
+
+		// Generate the blocks, the last transaction holds the remaining ones
+		let count = (chain.spec.blocks - batch * job_divide as usize).min(job_divide as usize);
+		let blocks: Vec<Block> = (0..count).map(|_| {
+			let block = chain.block(*tips.last().unwrap());
+			tips.push(block.next);
+			block
+		}).collect();
+		bytes += blocks.iter().flat_map(|block| &block.writes).map(|(_, key, value)| (key.len() + value.len()) as u64).sum::<u64>();
+
+		let sample = Sample::now();
+		let instant = Instant::now();
+		let mut rw_tx = db.begin_rw();
+		blocks.iter().flat_map(|block| &block.writes).for_each(|(table, key, value)| B::put(&mut rw_tx, &handles[*table], key, value));
+
+		timed_commit::<B>(rw_tx, Phase::Import, instant, latencies);
+		elapsed += instant.elapsed();
+		resources.add(&sample.elapsed());
+		pg.inc(count as u64);
+	});
+
+	let ops = chain.spec.blocks as u64;
+	measurements.push(TableMeasurement { table: "blocks".to_string(), phase: Phase::Import, seconds: elapsed.as_secs_f64(), ops, bytes, resources: Some(resources), threads: None });
+	tips
+}
+
+/// Reorganizations of the reorg workload, each replacing the blocks applied by the previous one
+pub const REORGS: usize = 10;
+
+/// Import `chain`, then `REORGS` times pop its top `reorg_depth` blocks in one transaction and apply the blocks of another fork in a second one
+pub fn benchmark_reorg<B: StorageBackend>(
+	pg: &ProgressBar,
+	db: &B,
+	chain: &Chain,
+	tables: &[TableSpec],
+	config: &ConfigSpec,
+	measurements: &mut Vec<TableMeasurement>,
+	latencies: &mut Latencies) -> ReorgReport
+{
+	let mut tips = benchmark_block_import(pg, db, chain, tables, config.job_divide, measurements, latencies);
+	let handles: Vec<B::Table<'_>> = tables.iter().map(|spec| db.open_table(spec)).collect();
+	// A scaled down chain can be shorter than `reorg_depth`
+	let depth = config.reorg_depth.min(tips.len() - 1);
+	let top = tips.len() - 1 - depth;
+
+	let mut report = ReorgReport { depth, pop_seconds: Vec::new(), reapply_seconds: Vec::new(), file_size_before: db.file_size(), file_size_after: 0 };
+	let (mut pop_resources, mut reapply_resources) = (ResourceUsage::zero(), ResourceUsage::zero());
+	let (mut pop_bytes, mut reapply_bytes) = (0, 0);
+
+	let mut current = chain.fork(0);
+	for reorg in 1..=REORGS as u64 {
+
+		// Blocks to pop, from the top, and the blocks of the other fork
+		let popped: Vec<Block> = (top..top + depth).rev().map(|height| current.block(tips[height])).collect();
+		current = chain.fork(reorg);
+		let applied: Vec<Block> = (top..top + depth).map(|height| {
+			let block = current.block(tips[height]);
+			tips[height + 1] = block.next;
+			block
+		}).collect();
+
+		let writes = |blocks: &[Block]| blocks.iter().flat_map(|block| &block.writes).map(|(_, key, value)| (key.len() + value.len()) as u64).sum::<u64>();
+		pop_bytes += writes(&popped);
+		reapply_bytes += writes(&applied);
+
+		let sample = Sample::now();
+		report.pop_seconds.push(write_blocks(db, &handles, &popped, true, latencies));
+		pop_resources.add(&sample.elapsed());
+
+		let sample = Sample::now();
+		report.reapply_seconds.push(write_blocks(db, &handles, &applied, false, latencies));
+		reapply_resources.add(&sample.elapsed());
+
+		pg.inc(2 * depth as u64);
+	}
+	report.file_size_after = db.file_size();
+
+	let ops = (REORGS * depth) as u64;
+	measurements.push(TableMeasurement { table: "blocks".to_string(), phase: Phase::Pop, seconds: report.pop_seconds.iter().sum(), ops, bytes: pop_bytes, resources: Some(pop_resources), threads: None });
+	measurements.push(TableMeasurement { table: "blocks".to_string(), phase: Phase::Reapply, seconds: report.reapply_seconds.iter().sum(), ops, bytes: reapply_bytes, resources: Some(reapply_resources), threads: None });
+	report
+}
+
+/// Delete every key of `blocks`, or put them, in one transaction. Returns its duration
+fn write_blocks<'db, B: StorageBackend>(db: &'db B, handles: &[B::Table<'db>], blocks: &[Block], delete: bool, latencies: &mut Latencies) -> f64 {
+	let instant = Instant::now();
+	let mut rw_tx = db.begin_rw();
+	blocks.iter().flat_map(|block| &block.writes).for_each(|(table, key, value)| match delete {
+		true => assert!(B::delete(&mut rw_tx, &handles[*table], key, None), "Popped block not found"),
+		false => B::put(&mut rw_tx, &handles[*table], key, value),
+	});
+
+	timed_commit::<B>(rw_tx, if delete { Phase::Pop } else { Phase::Reapply }, instant, latencies);
+	instant.elapsed().as_secs_f64()
+}
+
+/// Commit the transaction of `phase` started at `instant`, recording the duration of its writes and of its commit
+fn timed_commit<B: StorageBackend>(rw_tx: B::RwTxn<'_>, phase: Phase, instant: Instant, latencies: &mut Latencies) {
+	let commit = Instant::now();
+	let breakdown = B::commit(rw_tx);
+	latencies.record_write(phase, commit.duration_since(instant).as_nanos() as u64, commit.elapsed().as_nanos() as u64, breakdown);
+}
+
+/// RNG of one table for one iteration, derived from the seed of the run
+pub fn table_rng(seed: u64, iteration: u64, table: usize) -> StdRng {
+	StdRng::seed_from_u64(seed ^ (iteration << 32) ^ table as u64)
+}
+
+fn data_size(data: &[(Vec<u8>, Vec<u8>)]) -> u64 {
+	data.iter().map(|(key, value)| (key.len() + value.len()) as u64).sum()
+}